@@ -0,0 +1,86 @@
+//! The `alg` values that may be used with each key type and curve.
+
+use crate::Curve;
+
+/// The key agreement algorithms, these may be used with any curve that supports ECDH.
+const ECDH_ES: &[&str] = &[
+    "ECDH-ES",
+    "ECDH-ES+A128KW",
+    "ECDH-ES+A192KW",
+    "ECDH-ES+A256KW",
+];
+
+/// The `use` implied by the `alg`, or `None` for an unknown `alg`.
+///
+/// | `alg`                                            | `use` |
+/// |--------------------------------------------------|-------|
+/// | `ES*`, `EdDSA`, `RS*`, `PS*`, `HS*`              | `sig` |
+/// | `ECDH-ES`, `ECDH-ES+A*KW`, `RSA1_5`, `RSA-OAEP*` | `enc` |
+pub fn use_for_alg(alg: &str) -> Option<&'static str> {
+    match alg {
+        "ES256" | "ES384" | "ES512" | "ES256K" | "EdDSA" | "RS256" | "RS384" | "RS512"
+        | "PS256" | "PS384" | "PS512" | "HS256" | "HS384" | "HS512" => Some("sig"),
+        "RSA1_5" | "RSA-OAEP" | "RSA-OAEP-256" => Some("enc"),
+        alg if ECDH_ES.contains(&alg) => Some("enc"),
+        _ => None,
+    }
+}
+
+/// The JWS `alg` for signatures on the EC curve, JOSE registers none for the Brainpool curves.
+pub fn default_alg(curve: Curve) -> Option<&'static str> {
+    match curve {
+        Curve::P256 => Some("ES256"),
+        Curve::P384 => Some("ES384"),
+        Curve::P521 => Some("ES512"),
+        Curve::Secp256k1 => Some("ES256K"),
+        Curve::BrainpoolP256r1 | Curve::BrainpoolP384r1 | Curve::BrainpoolP512r1 => None,
+    }
+}
+
+/// If the `alg` may be used with a key on the EC curve, either the curve's signature algorithm or
+/// one of the key agreement algorithms.
+pub fn validate_alg(alg: &str, curve: Curve) -> bool {
+    default_alg(curve) == Some(alg) || ECDH_ES.contains(&alg)
+}
+
+/// The `alg` for the OKP `crv`, `EdDSA` for the signing curves and `ECDH-ES` for the key agreement
+/// curves.
+pub fn default_okp_alg(crv: &str) -> Option<&'static str> {
+    match crv {
+        "Ed25519" | "Ed448" => Some("EdDSA"),
+        "X25519" | "X448" => Some("ECDH-ES"),
+        _ => None,
+    }
+}
+
+/// If the `alg` may be used with a key on the OKP `crv`, the signing curves only sign and the key
+/// agreement curves only agree keys.
+pub fn validate_okp_alg(alg: &str, crv: &str) -> bool {
+    match crv {
+        "Ed25519" | "Ed448" => alg == "EdDSA",
+        "X25519" | "X448" => ECDH_ES.contains(&alg),
+        _ => false,
+    }
+}
+
+/// If the `alg` may be used with an RSA key, the PKCS#1 v1.5 and RSASSA-PSS signature algorithms
+/// or the RSA key management algorithms.
+pub fn validate_rsa_alg(alg: &str) -> bool {
+    matches!(
+        alg,
+        "RS256"
+            | "RS384"
+            | "RS512"
+            | "PS256"
+            | "PS384"
+            | "PS512"
+            | "RSA1_5"
+            | "RSA-OAEP"
+            | "RSA-OAEP-256"
+    )
+}
+
+/// If the `alg` may be used with a symmetric key, only the HMAC algorithms.
+pub fn validate_oct_alg(alg: &str) -> bool {
+    matches!(alg, "HS256" | "HS384" | "HS512")
+}
@@ -0,0 +1,75 @@
+use crate::{Jwk, error::Error};
+
+/// Sets the metadata members of a converted key, with the `alg` checked against the key when the
+/// JWK is built.
+///
+/// The builder applies the same setters as [`Jwk`], so the JWK serializes exactly as if they were
+/// called directly.
+#[derive(Debug)]
+pub struct JwkBuilder {
+    jwk: Jwk,
+    alg: Option<String>,
+    use_: Option<String>,
+    kid: Option<String>,
+    key_ops: Option<Vec<String>>,
+}
+
+impl JwkBuilder {
+    /// Start from a converted key, its derived members are kept unless replaced.
+    pub fn new(jwk: Jwk) -> Self {
+        Self {
+            jwk,
+            alg: None,
+            use_: None,
+            kid: None,
+            key_ops: None,
+        }
+    }
+
+    /// Replace the derived `alg`.
+    pub fn alg(mut self, alg: impl Into<String>) -> Self {
+        self.alg = Some(alg.into());
+        self
+    }
+
+    /// Set the intended `use`.
+    pub fn use_(mut self, use_: impl Into<String>) -> Self {
+        self.use_ = Some(use_.into());
+        self
+    }
+
+    /// Replace the computed `kid`.
+    pub fn kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// Set the permitted `key_ops`.
+    pub fn key_ops<I, S>(mut self, key_ops: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.key_ops = Some(key_ops.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Build the JWK, failing if the `alg` may not be used with the key, see [`Jwk::set_alg`].
+    pub fn build(self) -> Result<Jwk, Error> {
+        let mut jwk = self.jwk;
+        if let Some(alg) = self.alg {
+            jwk.set_alg(alg)?;
+        }
+        if let Some(use_) = self.use_ {
+            jwk.set_use(use_);
+        }
+        if let Some(key_ops) = self.key_ops {
+            jwk.set_key_ops(key_ops);
+        }
+        if let Some(kid) = self.kid {
+            jwk.set_kid(kid);
+        }
+
+        Ok(jwk)
+    }
+}
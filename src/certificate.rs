@@ -0,0 +1,219 @@
+use base64ct::{Base64, Base64UrlUnpadded, Encoding};
+use openssl::{
+    sha::{sha1, sha256},
+    x509::{X509, X509Ref},
+};
+
+use crate::{
+    Jwk, ThumbprintHash,
+    error::{Error, ResultExt},
+    rsa::der_header,
+};
+
+/// The keyUsage extension object identifier, 2.5.29.15.
+const KEY_USAGE_OID: &[u8] = &[0x55, 0x1d, 0x0f];
+
+/// The extKeyUsage extension object identifier, 2.5.29.37.
+const EXT_KEY_USAGE_OID: &[u8] = &[0x55, 0x1d, 0x25];
+
+/// The extKeyUsage purposes that only sign: serverAuth, clientAuth, codeSigning, timeStamping, and
+/// OCSPSigning.
+const SIGNING_PURPOSES: [&[u8]; 5] = [
+    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01],
+    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x02],
+    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x03],
+    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x08],
+    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x09],
+];
+
+/// Convert the public key of a PEM encoded X.509 certificate to its JWK.
+pub fn certificate_pem_to_jwk(pem: &[u8]) -> Result<Jwk, Error> {
+    let certificate = X509::from_pem(pem).parse_err("certificate PEM")?;
+    certificate_to_jwk(&certificate)
+}
+
+/// Convert the public key of a DER encoded X.509 certificate to its JWK.
+pub fn certificate_der_to_jwk(der: &[u8]) -> Result<Jwk, Error> {
+    let certificate = X509::from_der(der).parse_err("certificate DER")?;
+    certificate_to_jwk(&certificate)
+}
+
+/// Convert the public key of a certificate to its JWK, the `use` is derived from the keyUsage or
+/// extKeyUsage extension of the certificate.
+pub(crate) fn certificate_to_jwk(certificate: &X509Ref) -> Result<Jwk, Error> {
+    let key = certificate
+        .public_key()
+        .backend_err("get certificate public key")?;
+    let mut jwk = Jwk::try_from(key.as_ref())?;
+
+    let der = certificate.to_der().backend_err("encode certificate DER")?;
+    if let Some(use_) = key_usage_use(&der) {
+        jwk.set_use(use_.to_string());
+    }
+
+    Ok(jwk)
+}
+
+/// Build the `x5c` chain from PEM encoded certificates, in the order they appear.
+pub fn x5c_from_pem(pem: &[u8]) -> Result<Vec<String>, Error> {
+    let certificates = X509::stack_from_pem(pem).parse_err("certificate PEM")?;
+
+    certificates
+        .iter()
+        .map(|certificate| x5c_entry(certificate))
+        .collect()
+}
+
+/// Attach PEM encoded certificates to the keys they certify, matching their public keys by
+/// thumbprint. A matching certificate becomes the `x5c` of the first key without one, setting its
+/// thumbprints and `use` as [`Jwk::set_x5c`] does.
+///
+/// Returns the indices of the certificates that were not attached, with the error for each that
+/// could not be parsed or converted, so one bad certificate does not fail the bundle.
+pub fn attach_certificates(
+    keys: &mut [Jwk],
+    certificates: &[&[u8]],
+) -> Result<Vec<(usize, Option<Error>)>, Error> {
+    let mut thumbprints = keys
+        .iter()
+        .map(|key| key.thumbprint(ThumbprintHash::Sha256).map(Some))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut unmatched = Vec::new();
+    for (index, pem) in certificates.iter().enumerate() {
+        let parsed = X509::from_pem(pem)
+            .parse_err("certificate PEM")
+            .and_then(|certificate| {
+                let thumbprint =
+                    certificate_to_jwk(&certificate)?.thumbprint(ThumbprintHash::Sha256)?;
+                Ok((certificate, thumbprint))
+            });
+        let (certificate, thumbprint) = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                unmatched.push((index, Some(error)));
+                continue;
+            }
+        };
+
+        match thumbprints
+            .iter_mut()
+            .position(|key| key.as_ref() == Some(&thumbprint))
+        {
+            Some(key) => {
+                thumbprints[key] = None;
+                keys[key].set_x5c(vec![x5c_entry(&certificate)?])?;
+            }
+            None => unmatched.push((index, None)),
+        }
+    }
+
+    Ok(unmatched)
+}
+
+/// Encode a certificate as an `x5c` entry, the standard padded base64 of its DER.
+pub(crate) fn x5c_entry(certificate: &X509Ref) -> Result<String, Error> {
+    let der = certificate.to_der().backend_err("encode certificate DER")?;
+
+    Ok(Base64::encode_string(&der))
+}
+
+/// Decode an `x5c` entry to the certificate DER.
+pub(crate) fn decode_x5c_entry(entry: &str) -> Result<Vec<u8>, Error> {
+    Base64::decode_vec(entry).map_err(|source| Error::Encoding {
+        name: "`x5c` entry".to_string(),
+        source,
+    })
+}
+
+/// Compute the `x5t` and `x5t#S256` thumbprints of a certificate, the base64url SHA-1 and SHA-256
+/// digests of its DER.
+pub(crate) fn x5t(der: &[u8]) -> (String, String) {
+    (
+        Base64UrlUnpadded::encode_string(&sha1(der)),
+        Base64UrlUnpadded::encode_string(&sha256(der)),
+    )
+}
+
+/// The `use` implied by the extensions of a DER encoded certificate. The keyUsage extension gives
+/// `sig` for digitalSignature and `enc` for keyEncipherment or keyAgreement. Without a keyUsage
+/// extension, an extKeyUsage of only signing purposes, such as serverAuth or codeSigning, gives
+/// `sig`. `None` if the extensions are absent or imply both.
+pub(crate) fn key_usage_use(der: &[u8]) -> Option<&'static str> {
+    let (is_signing, is_encryption) = match extension_value(der, KEY_USAGE_OID) {
+        Some(value) => {
+            let (tag, bits, _) = split_element(value)?;
+            if tag != 0x03 {
+                return None;
+            }
+            // The first content byte is the count of unused bits, the usages start after it.
+            let usages = *bits.get(1)?;
+            (usages & 0x80 != 0, usages & (0x20 | 0x08) != 0)
+        }
+        None => {
+            let (tag, mut purposes, _) = split_element(extension_value(der, EXT_KEY_USAGE_OID)?)?;
+            if tag != 0x30 || purposes.is_empty() {
+                return None;
+            }
+            let mut is_signing = true;
+            while !purposes.is_empty() {
+                let (_, purpose, rest) = split_element(purposes)?;
+                is_signing &= SIGNING_PURPOSES.contains(&purpose);
+                purposes = rest;
+            }
+            (is_signing, false)
+        }
+    };
+
+    match (is_signing, is_encryption) {
+        (true, false) => Some("sig"),
+        (false, true) => Some("enc"),
+        _ => None,
+    }
+}
+
+/// The contents of the `extnValue` of the extension with the object identifier, walking the
+/// TBSCertificate of a DER encoded certificate to its extensions.
+fn extension_value<'a>(der: &'a [u8], oid: &[u8]) -> Option<&'a [u8]> {
+    let (_, certificate, _) = split_element(der)?;
+    let (_, mut fields, _) = split_element(certificate)?;
+
+    // The extensions are the explicitly tagged `[3]` field, after the subject public key info and
+    // the optional unique identifiers.
+    let extensions = loop {
+        let (tag, contents, rest) = split_element(fields)?;
+        if tag == 0xa3 {
+            break contents;
+        }
+        fields = rest;
+    };
+
+    let (_, mut extensions, _) = split_element(extensions)?;
+    while !extensions.is_empty() {
+        let (_, extension, rest) = split_element(extensions)?;
+        extensions = rest;
+
+        let (_, id, rest) = split_element(extension)?;
+        if id != oid {
+            continue;
+        }
+        // Skip the critical flag, if present, to the OCTET STRING.
+        let (mut tag, mut value, rest) = split_element(rest)?;
+        if tag == 0x01 {
+            (tag, value, _) = split_element(rest)?;
+        }
+        return (tag == 0x04).then_some(value);
+    }
+
+    None
+}
+
+/// Split the first DER element from the input into its tag, its contents, and the rest of the
+/// input.
+fn split_element(der: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *der.first()?;
+    let (header, length) = der_header(der)?;
+    let contents = der.get(header..header.checked_add(length)?)?;
+
+    Some((tag, contents, &der[header + length..]))
+}
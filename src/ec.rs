@@ -0,0 +1,437 @@
+use std::collections::BTreeMap;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+#[cfg(feature = "openssl")]
+use openssl::{
+    bn::{BigNum, BigNumContext},
+    ec::{EcGroup, EcGroupRef, EcKey, EcKeyRef, EcPoint, PointConversionForm},
+    nid::Nid,
+    pkey::{PKey, Private, Public},
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "openssl")]
+use crate::{PemFormat, decode_big_num, error::ResultExt};
+use crate::{alg::default_alg, decode_member, error::Error, hex, thumbprint};
+
+/// A supported elliptic curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// NIST P-256.
+    P256,
+    /// NIST P-384.
+    P384,
+    /// NIST P-521.
+    P521,
+    /// The Koblitz curve used by Bitcoin.
+    Secp256k1,
+    /// Brainpool P-256 r1.
+    BrainpoolP256r1,
+    /// Brainpool P-384 r1.
+    BrainpoolP384r1,
+    /// Brainpool P-512 r1.
+    BrainpoolP512r1,
+}
+
+impl Curve {
+    /// Every supported curve.
+    #[cfg(feature = "openssl")]
+    pub(crate) const ALL: [Self; 7] = [
+        Self::P256,
+        Self::P384,
+        Self::P521,
+        Self::Secp256k1,
+        Self::BrainpoolP256r1,
+        Self::BrainpoolP384r1,
+        Self::BrainpoolP512r1,
+    ];
+
+    /// The curve for the OpenSSL curve name, if it is supported.
+    #[cfg(feature = "openssl")]
+    pub(crate) fn from_nid(nid: Nid) -> Option<Self> {
+        match nid {
+            Nid::X9_62_PRIME256V1 => Some(Self::P256),
+            Nid::SECP384R1 => Some(Self::P384),
+            Nid::SECP521R1 => Some(Self::P521),
+            Nid::SECP256K1 => Some(Self::Secp256k1),
+            Nid::BRAINPOOL_P256R1 => Some(Self::BrainpoolP256r1),
+            Nid::BRAINPOOL_P384R1 => Some(Self::BrainpoolP384r1),
+            Nid::BRAINPOOL_P512R1 => Some(Self::BrainpoolP512r1),
+            _ => None,
+        }
+    }
+
+    /// The curve for the JWK `crv`, if it is supported.
+    pub fn from_crv(crv: &str) -> Option<Self> {
+        match crv {
+            "P-256" => Some(Self::P256),
+            "P-384" => Some(Self::P384),
+            "P-521" => Some(Self::P521),
+            "secp256k1" => Some(Self::Secp256k1),
+            "BP-256" => Some(Self::BrainpoolP256r1),
+            "BP-384" => Some(Self::BrainpoolP384r1),
+            "BP-512" => Some(Self::BrainpoolP512r1),
+            _ => None,
+        }
+    }
+
+    /// The OpenSSL curve name.
+    #[cfg(feature = "openssl")]
+    pub(crate) fn nid(self) -> Nid {
+        match self {
+            Self::P256 => Nid::X9_62_PRIME256V1,
+            Self::P384 => Nid::SECP384R1,
+            Self::P521 => Nid::SECP521R1,
+            Self::Secp256k1 => Nid::SECP256K1,
+            Self::BrainpoolP256r1 => Nid::BRAINPOOL_P256R1,
+            Self::BrainpoolP384r1 => Nid::BRAINPOOL_P384R1,
+            Self::BrainpoolP512r1 => Nid::BRAINPOOL_P512R1,
+        }
+    }
+
+    /// The JWK `crv` of the curve.
+    pub fn crv_str(self) -> &'static str {
+        match self {
+            Self::P256 => "P-256",
+            Self::P384 => "P-384",
+            Self::P521 => "P-521",
+            Self::Secp256k1 => "secp256k1",
+            Self::BrainpoolP256r1 => "BP-256",
+            Self::BrainpoolP384r1 => "BP-384",
+            Self::BrainpoolP512r1 => "BP-512",
+        }
+    }
+
+    /// The size of the curve in bits.
+    pub fn bits(self) -> usize {
+        match self {
+            Self::P256 | Self::Secp256k1 | Self::BrainpoolP256r1 => 256,
+            Self::P384 | Self::BrainpoolP384r1 => 384,
+            Self::BrainpoolP512r1 => 512,
+            Self::P521 => 521,
+        }
+    }
+
+    /// The size in bytes of a field element for the curve, coordinates are left-padded to this
+    /// size.
+    pub fn field_size(self) -> i32 {
+        match self {
+            Self::P256 | Self::Secp256k1 | Self::BrainpoolP256r1 => 32,
+            Self::P384 | Self::BrainpoolP384r1 => 48,
+            Self::BrainpoolP512r1 => 64,
+            Self::P521 => 66,
+        }
+    }
+}
+
+/// An elliptic curve JWK.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EcOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    pub x: String,
+    pub y: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    pub crv: String,
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alg: String,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+    /// The base64url compressed SEC1 encoding of the public point, this is not a JWK member.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<String>,
+    /// The size of the curve in bits, this is not a JWK member.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bits: Option<usize>,
+}
+
+impl EcOutput {
+    /// Create the public JWK from the padded affine coordinates of the key.
+    pub(crate) fn from_coordinates(curve: Curve, x: &[u8], y: &[u8]) -> Result<Self, Error> {
+        debug!(crv = curve.crv_str(), "detected curve");
+
+        let base64_x = Base64UrlUnpadded::encode_string(x);
+        let base64_y = Base64UrlUnpadded::encode_string(y);
+
+        let kid = thumbprint(BTreeMap::from([
+            ("crv", curve.crv_str()),
+            ("kty", "EC"),
+            ("x", &base64_x),
+            ("y", &base64_y),
+        ]))?;
+
+        Ok(EcOutput {
+            d: None,
+            x: base64_x,
+            y: base64_y,
+            kty: "EC".to_string(),
+            alg: default_alg(curve).unwrap_or_default().to_string(),
+            crv: curve.crv_str().to_string(),
+            kid: Some(kid),
+            use_: None,
+            key_ops: None,
+            ext: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            point: None,
+            bits: None,
+        })
+    }
+
+    /// The compressed SEC1 encoding of the public point.
+    pub fn compressed_point(&self) -> Result<Vec<u8>, Error> {
+        self.validate()?;
+        let curve =
+            Curve::from_crv(&self.crv).ok_or_else(|| Error::UnsupportedCurve(self.crv.clone()))?;
+
+        let x = padded_member(&self.x, "x", curve)?;
+        let y = padded_member(&self.y, "y", curve)?;
+        let parity = y.last().map_or(0, |byte| byte & 1);
+
+        let mut point = vec![0x02 | parity];
+        point.extend_from_slice(&x);
+        Ok(point)
+    }
+
+    /// The `x`, `y`, and `d` members as lowercase hex left-padded to the field size of the curve, one
+    /// `name: value` line each, for comparing against tools that report coordinates in hex.
+    pub fn coordinates_hex(&self) -> Result<String, Error> {
+        let curve =
+            Curve::from_crv(&self.crv).ok_or_else(|| Error::UnsupportedCurve(self.crv.clone()))?;
+
+        let mut output = String::new();
+        for (name, value) in [
+            ("x", Some(&self.x)),
+            ("y", Some(&self.y)),
+            ("d", self.d.as_ref()),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            let padded = padded_member(value, name, curve)?;
+            output.push_str(&format!("{name}: {}\n", hex(&padded)));
+        }
+
+        Ok(output)
+    }
+
+    /// Check that the public point lies on the curve.
+    #[cfg(feature = "openssl")]
+    pub fn validate(&self) -> Result<(), Error> {
+        let curve =
+            Curve::from_crv(&self.crv).ok_or_else(|| Error::UnsupportedCurve(self.crv.clone()))?;
+        let group = EcGroup::from_curve_name(curve.nid()).backend_err("create curve")?;
+
+        let x = decode_big_num(&self.x, "x")?;
+        let y = decode_big_num(&self.y, "y")?;
+        let mut ctx = BigNumContext::new().backend_err("create big number")?;
+        let mut point = EcPoint::new(&group).backend_err("create point")?;
+
+        // OpenSSL refuses to set coordinates that are off the curve, so either failing means the
+        // point is invalid.
+        let is_on_curve = point
+            .set_affine_coordinates_gfp(&group, &x, &y, &mut ctx)
+            .is_ok()
+            && point.is_on_curve(&group, &mut ctx).unwrap_or(false);
+        if !is_on_curve {
+            return Err(Error::InvalidPoint(self.crv.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the public point lies on the curve.
+    #[cfg(not(feature = "openssl"))]
+    pub fn validate(&self) -> Result<(), Error> {
+        let curve =
+            Curve::from_crv(&self.crv).ok_or_else(|| Error::UnsupportedCurve(self.crv.clone()))?;
+
+        let x = padded_member(&self.x, "x", curve)?;
+        let y = padded_member(&self.y, "y", curve)?;
+        if !crate::rustcrypto::is_on_curve(curve, &x, &y)? {
+            return Err(Error::InvalidPoint(self.crv.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Reconstruct the key as a PEM, a PKCS#8 private key if `d` is present.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem(&self) -> Result<Vec<u8>, Error> {
+        self.to_pem_as(PemFormat::Pkcs8)
+    }
+
+    /// Reconstruct the key as a PEM, a private key is SEC1 in the traditional format.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem_as(&self, format: PemFormat) -> Result<Vec<u8>, Error> {
+        let curve =
+            Curve::from_crv(&self.crv).ok_or_else(|| Error::UnsupportedCurve(self.crv.clone()))?;
+        let group = EcGroup::from_curve_name(curve.nid()).backend_err("create curve")?;
+
+        let x = decode_big_num(&self.x, "x")?;
+        let y = decode_big_num(&self.y, "y")?;
+        let public_key = EcKey::from_public_key_affine_coordinates(&group, &x, &y)
+            .backend_err("reconstruct public key")?;
+
+        match &self.d {
+            Some(d) => {
+                let d = decode_big_num(d, "d")?;
+                let private_key =
+                    EcKey::from_private_components(&group, &d, public_key.public_key())
+                        .backend_err("reconstruct private key")?;
+                match format {
+                    PemFormat::Pkcs8 => PKey::from_ec_key(private_key)
+                        .backend_err("wrap private key")?
+                        .private_key_to_pem_pkcs8(),
+                    PemFormat::Traditional => private_key.private_key_to_pem(),
+                }
+                .backend_err("encode private key PEM")
+            }
+            None => PKey::from_ec_key(public_key)
+                .backend_err("wrap public key")?
+                .public_key_to_pem()
+                .backend_err("encode public key PEM"),
+        }
+    }
+}
+
+/// Decode a coordinate or scalar member, left-padded to the field size of the curve.
+fn padded_member(value: &str, member: &str, curve: Curve) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let size = usize::try_from(curve.field_size()).unwrap_or_default();
+    let value = Zeroizing::new(decode_member(value, member)?);
+    let start = value
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(value.len());
+    let value = &value[start..];
+    if value.len() > size {
+        return Err(Error::CoordinateTooLarge {
+            member: member.to_string(),
+            curve: curve.crv_str().to_string(),
+        });
+    }
+
+    let mut padded = Zeroizing::new(Vec::with_capacity(size));
+    padded.resize(size - value.len(), 0);
+    padded.extend_from_slice(value);
+    Ok(padded)
+}
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&EcKeyRef<Public>> for EcOutput {
+    type Error = Error;
+
+    fn try_from(key: &EcKeyRef<Public>) -> Result<Self, Self::Error> {
+        let mut ctx = BigNumContext::new().backend_err("create big number")?;
+        let mut x = BigNum::new().backend_err("create big number")?;
+        let mut y = BigNum::new().backend_err("create big number")?;
+
+        key.public_key()
+            .affine_coordinates(key.group(), &mut x, &mut y, &mut ctx)
+            .backend_err("extract coordinates")?;
+
+        let curve = curve(key.group())?;
+
+        let x = x
+            .to_vec_padded(curve.field_size())
+            .backend_err("pad x coordinate")?;
+        let y = y
+            .to_vec_padded(curve.field_size())
+            .backend_err("pad y coordinate")?;
+
+        Self::from_coordinates(curve, &x, &y)
+    }
+}
+
+/// The supported curve of the group, a group with explicit parameters is matched against the
+/// parameters of the supported curves.
+#[cfg(feature = "openssl")]
+fn curve(group: &EcGroupRef) -> Result<Curve, Error> {
+    let Some(nid) = group.curve_name() else {
+        return explicit_curve(group)?.ok_or(Error::ExplicitCurve);
+    };
+    Curve::from_nid(nid)
+        .ok_or_else(|| Error::UnsupportedCurve(nid.short_name().unwrap_or("unknown").to_string()))
+}
+
+/// The supported curve with the same field, equation, generator, and order as the group.
+#[cfg(feature = "openssl")]
+fn explicit_curve(group: &EcGroupRef) -> Result<Option<Curve>, Error> {
+    let mut ctx = BigNumContext::new().backend_err("create big number")?;
+    let explicit = group_parameters(group, &mut ctx)?;
+
+    for curve in Curve::ALL {
+        let named = EcGroup::from_curve_name(curve.nid()).backend_err("create curve")?;
+        if group_parameters(&named, &mut ctx)? == explicit {
+            return Ok(Some(curve));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The field prime, equation coefficients, order, and uncompressed generator of the group.
+#[cfg(feature = "openssl")]
+fn group_parameters(group: &EcGroupRef, ctx: &mut BigNumContext) -> Result<[Vec<u8>; 5], Error> {
+    let mut p = BigNum::new().backend_err("create big number")?;
+    let mut a = BigNum::new().backend_err("create big number")?;
+    let mut b = BigNum::new().backend_err("create big number")?;
+    let mut order = BigNum::new().backend_err("create big number")?;
+    group
+        .components_gfp(&mut p, &mut a, &mut b, ctx)
+        .backend_err("get curve parameters")?;
+    group
+        .order(&mut order, ctx)
+        .backend_err("get curve order")?;
+    let generator = match group.generator_opt() {
+        Some(generator) => generator
+            .to_bytes(group, PointConversionForm::UNCOMPRESSED, ctx)
+            .backend_err("encode generator")?,
+        None => Vec::new(),
+    };
+
+    Ok([
+        p.to_vec(),
+        a.to_vec(),
+        b.to_vec(),
+        order.to_vec(),
+        generator,
+    ])
+}
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&EcKeyRef<Private>> for EcOutput {
+    type Error = Error;
+
+    fn try_from(key: &EcKeyRef<Private>) -> Result<Self, Self::Error> {
+        let public_key = EcKey::from_public_key(key.group(), key.public_key())
+            .backend_err("extract public key")?;
+        let mut output = Self::try_from(public_key.as_ref())?;
+
+        let d = Zeroizing::new(
+            key.private_key()
+                .to_vec_padded(curve(key.group())?.field_size())
+                .backend_err("pad private scalar")?,
+        );
+
+        let base64_d = Base64UrlUnpadded::encode_string(&d);
+        output.d = Some(base64_d);
+
+        Ok(output)
+    }
+}
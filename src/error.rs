@@ -0,0 +1,165 @@
+//! Errors produced while converting a key.
+
+/// A boxed error from one of the cryptography backends.
+pub type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// An error produced while converting a key.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input could not be parsed.
+    #[error("the {input} could not be parsed")]
+    Parse {
+        /// What was being parsed, such as `PEM` or `PKCS#12`.
+        input: &'static str,
+        /// The error from the parser.
+        #[source]
+        source: BoxError,
+    },
+
+    /// A value is not valid base64.
+    #[error("the {name} is not valid base64")]
+    Encoding {
+        /// The value being decoded, such as the `x` member.
+        name: String,
+        /// The error from the decoder.
+        #[source]
+        source: base64ct::Error,
+    },
+
+    /// The cryptography backend failed.
+    #[error("failed to {operation}")]
+    Backend {
+        /// The operation that failed.
+        operation: &'static str,
+        /// The error from the backend.
+        #[source]
+        source: BoxError,
+    },
+
+    /// The JWK could not be serialized.
+    #[error("the JWK could not be serialized")]
+    Serialize(#[from] serde_json::Error),
+
+    /// The private key is encrypted but no passphrase was provided.
+    #[error("the private key is encrypted, a passphrase is required")]
+    EncryptedKey,
+
+    /// The input is a private key when a public key was expected, or the reverse.
+    #[error("the key is a {found} key, not a {expected} key")]
+    UnexpectedKeyKind {
+        /// The kind of key that was expected, `public` or `private`.
+        expected: &'static str,
+        /// The kind of key that was found.
+        found: &'static str,
+    },
+
+    /// The PEM does not contain a key block.
+    #[error("the PEM does not contain a key")]
+    MissingPemKey,
+
+    /// The PKCS#12 archive does not contain a private key.
+    #[error("the PKCS#12 archive does not contain a private key")]
+    MissingPrivateKey,
+
+    /// The key type is not supported.
+    #[error("the key type `{0}` is not supported")]
+    UnsupportedKeyType(String),
+
+    /// The curve is not supported.
+    #[error("the curve `{0}` is not supported")]
+    UnsupportedCurve(String),
+
+    /// The EC key gives its curve by explicit parameters that do not match a supported curve.
+    #[error(
+        "the curve is given by explicit parameters that match no supported curve, re-encode the key with a named curve"
+    )]
+    ExplicitCurve,
+
+    /// The public point does not lie on the curve.
+    #[error("the public point does not lie on the curve `{0}`")]
+    InvalidPoint(String),
+
+    /// An EC coordinate or scalar member is larger than the field of its curve.
+    #[error("the `{member}` member is larger than the field of the curve `{curve}`")]
+    CoordinateTooLarge {
+        /// The member, such as `x`.
+        member: String,
+        /// The curve of the key.
+        curve: String,
+    },
+
+    /// The private members of an RSA key do not agree with each other or the public members.
+    #[error("the RSA private key is inconsistent, {0}")]
+    InconsistentRsaKey(&'static str),
+
+    /// A raw key is not the length of a key on its curve.
+    #[error("a raw `{crv}` key must be {expected} bytes, found {length} bytes")]
+    InvalidKeyLength {
+        /// The curve of the key.
+        crv: String,
+        /// The length of a key on the curve.
+        expected: usize,
+        /// The length of the raw key.
+        length: usize,
+    },
+
+    /// The symmetric secret is empty.
+    #[error("the symmetric secret is empty")]
+    EmptySecret,
+
+    /// The JWK is missing a member that is required to reconstruct the key.
+    #[error("the JWK is missing the `{0}` member")]
+    MissingMember(&'static str),
+
+    /// The `alg` cannot be used with the key type.
+    #[error("the alg `{alg}` cannot be used with a `{kty}` key")]
+    InvalidAlg {
+        /// The rejected `alg`.
+        alg: String,
+        /// The `kty` of the key.
+        kty: &'static str,
+    },
+
+    /// The RSA modulus is smaller than required.
+    #[error("the RSA modulus is {bits} bits, at least {min_bits} bits are required")]
+    WeakRsaKey {
+        /// The size of the modulus.
+        bits: usize,
+        /// The required size of the modulus.
+        min_bits: usize,
+    },
+
+    /// The `alg` of the key type cannot be derived from the key.
+    #[error("the alg of a `{0}` key cannot be derived from the key, an alg must be provided")]
+    AmbiguousAlg(&'static str),
+
+    /// The OpenSSH public key is malformed.
+    #[error("the SSH public key is invalid, {0}")]
+    InvalidSshKey(&'static str),
+}
+
+/// Attach what was being done to a backend error.
+pub(crate) trait ResultExt<T> {
+    /// Map the error to [`Error::Parse`].
+    fn parse_err(self, input: &'static str) -> Result<T, Error>;
+
+    /// Map the error to [`Error::Backend`].
+    fn backend_err(self, operation: &'static str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<BoxError>> ResultExt<T> for Result<T, E> {
+    fn parse_err(self, input: &'static str) -> Result<T, Error> {
+        self.map_err(|source| Error::Parse {
+            input,
+            source: source.into(),
+        })
+    }
+
+    fn backend_err(self, operation: &'static str) -> Result<T, Error> {
+        self.map_err(|source| Error::Backend {
+            operation,
+            source: source.into(),
+        })
+    }
+}
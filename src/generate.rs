@@ -0,0 +1,52 @@
+use openssl::{
+    ec::{EcGroup, EcKey},
+    pkey::{Id, PKey},
+    rsa::Rsa,
+};
+
+use crate::{
+    Curve, Jwk,
+    error::{Error, ResultExt},
+    okp::okp_id,
+};
+
+/// The key to generate.
+#[derive(Debug, Clone, Copy)]
+pub enum KeySpec<'a> {
+    /// An elliptic curve key on the `crv`.
+    Ec(&'a str),
+    /// An RSA key with a modulus of this many bits.
+    Rsa(u32),
+    /// An octet key pair on the `crv`.
+    Okp(&'a str),
+}
+
+/// Generate a fresh private key, returning its JWK and PKCS#8 PEM.
+pub fn generate(spec: KeySpec<'_>) -> Result<(Jwk, Vec<u8>), Error> {
+    let key = match spec {
+        KeySpec::Ec(crv) => {
+            let curve =
+                Curve::from_crv(crv).ok_or_else(|| Error::UnsupportedCurve(crv.to_string()))?;
+            let group = EcGroup::from_curve_name(curve.nid()).backend_err("create curve")?;
+            let key = EcKey::generate(&group).backend_err("generate key")?;
+            PKey::from_ec_key(key)
+        }
+        KeySpec::Rsa(bits) => {
+            let key = Rsa::generate(bits).backend_err("generate key")?;
+            PKey::from_rsa(key)
+        }
+        KeySpec::Okp(crv) => match okp_id(crv)? {
+            Id::ED25519 => PKey::generate_ed25519(),
+            Id::ED448 => PKey::generate_ed448(),
+            Id::X25519 => PKey::generate_x25519(),
+            _ => PKey::generate_x448(),
+        },
+    }
+    .backend_err("generate key")?;
+
+    let pem = key
+        .private_key_to_pem_pkcs8()
+        .backend_err("encode private key PEM")?;
+
+    Ok((Jwk::try_from(key.as_ref())?, pem))
+}
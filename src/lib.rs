@@ -0,0 +1,940 @@
+//! Convert a PEM encoded key to its parameters, generally for use as a JWK.
+//!
+//! The conversions take the encoded key bytes and return a [`Jwk`] or its JSON, reading files is
+//! left to the caller.
+//!
+//! The keys are converted with OpenSSL by default. With the `rustcrypto` feature PEM and DER keys
+//! are parsed with the RustCrypto crates instead, and without the `openssl` feature the crate does
+//! not depend on OpenSSL at all. Certificates, PKCS#12 archives, encrypted keys, generating keys,
+//! reconstructing a PEM, and the conversions from OpenSSL key types such as
+//! `TryFrom<&PKeyRef<Public>>` are only available with the `openssl` feature. The byte slice
+//! conversions like [`pem_to_jwk`] and [`pem_to_json`] remain, so the library builds for
+//! `wasm32-unknown-unknown` with `--no-default-features --features rustcrypto`.
+
+#[cfg(not(any(feature = "openssl", feature = "rustcrypto")))]
+compile_error!("either the `openssl` or the `rustcrypto` feature must be enabled");
+
+pub mod alg;
+pub mod error;
+pub mod schema;
+
+mod builder;
+#[cfg(feature = "openssl")]
+mod certificate;
+mod ec;
+#[cfg(feature = "openssl")]
+mod generate;
+mod oct;
+mod okp;
+mod pem;
+mod rsa;
+#[cfg(feature = "rustcrypto")]
+mod rustcrypto;
+mod sorted;
+mod ssh;
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+#[cfg(feature = "openssl")]
+use openssl::{
+    bn::BigNum,
+    ec::EcKeyRef,
+    nid::Nid,
+    pkcs12::Pkcs12,
+    pkey::{Id, PKey, PKeyRef, Private, Public},
+    rsa::RsaRef,
+    sha::{sha256, sha384, sha512},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openssl")]
+use serde_json::Value;
+#[cfg(feature = "openssl")]
+use tracing::debug;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "openssl")]
+use crate::{
+    certificate::{decode_x5c_entry, key_usage_use, x5c_entry, x5t},
+    okp::okp_id,
+    rsa::pss_alg,
+};
+use crate::{error::ResultExt, okp::key_length, pem::is_encrypted_pem};
+
+#[cfg(not(feature = "openssl"))]
+use crate::rustcrypto::{digest, parse_raw_okp};
+#[cfg(feature = "rustcrypto")]
+use crate::rustcrypto::{parse_der, parse_pem};
+
+pub use builder::JwkBuilder;
+#[cfg(feature = "openssl")]
+pub use certificate::{
+    attach_certificates, certificate_der_to_jwk, certificate_pem_to_jwk, x5c_from_pem,
+};
+pub use ec::{Curve, EcOutput};
+pub use error::Error;
+#[cfg(feature = "openssl")]
+pub use generate::{KeySpec, generate};
+pub use oct::OctOutput;
+pub use okp::OkpOutput;
+pub use pem::split_pem;
+pub use rsa::RsaOutput;
+pub use sorted::{MEMBER_ORDER, SortedJwk, SortedJwkSet};
+pub use ssh::ssh_to_jwk;
+
+/// The registered JOSE `alg` values for signatures and key management.
+pub const ALGORITHMS: &[&str] = &[
+    "HS256",
+    "HS384",
+    "HS512",
+    "RS256",
+    "RS384",
+    "RS512",
+    "ES256",
+    "ES384",
+    "ES512",
+    "ES256K",
+    "PS256",
+    "PS384",
+    "PS512",
+    "EdDSA",
+    "RSA1_5",
+    "RSA-OAEP",
+    "RSA-OAEP-256",
+    "A128KW",
+    "A192KW",
+    "A256KW",
+    "dir",
+    "ECDH-ES",
+    "ECDH-ES+A128KW",
+    "ECDH-ES+A192KW",
+    "ECDH-ES+A256KW",
+    "A128GCMKW",
+    "A192GCMKW",
+    "A256GCMKW",
+    "PBES2-HS256+A128KW",
+    "PBES2-HS384+A192KW",
+    "PBES2-HS512+A256KW",
+];
+
+/// The registered JWK `key_ops` values.
+pub const KEY_OPERATIONS: &[&str] = &[
+    "sign",
+    "verify",
+    "encrypt",
+    "decrypt",
+    "wrapKey",
+    "unwrapKey",
+    "deriveKey",
+    "deriveBits",
+];
+
+/// A JWK for any of the supported key types.
+///
+/// The members serialize in the declaration order of the key's struct, wrap it in a [`SortedJwk`]
+/// to serialize them in the canonical [`MEMBER_ORDER`] instead.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Jwk {
+    /// An elliptic curve key.
+    Ec(EcOutput),
+    /// An RSA key.
+    Rsa(RsaOutput),
+    /// An octet key pair.
+    Okp(OkpOutput),
+    /// A symmetric key.
+    Oct(OctOutput),
+}
+
+/// How the `kid` of a key is produced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KidFormat {
+    /// The RFC 7638 base64url thumbprint.
+    #[default]
+    Thumbprint,
+    /// The RFC 7638 thumbprint as a lowercase hex digest, SHA-256 unless
+    /// [`Jwk::set_kid_hash`] chose another hash.
+    Hex,
+    /// The RFC 9278 thumbprint URI,
+    /// `urn:ietf:params:oauth:jwk-thumbprint:<hash>:<thumbprint>`.
+    ThumbprintUri,
+    /// No `kid`.
+    None,
+}
+
+/// The encoding of a reconstructed PEM.
+#[cfg(feature = "openssl")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PemFormat {
+    /// PKCS#8 private keys and SubjectPublicKeyInfo public keys.
+    #[default]
+    Pkcs8,
+    /// The key type's own encoding, SEC1 for EC private keys and PKCS#1 for RSA keys. The other
+    /// keys have no such encoding and use PKCS#8.
+    Traditional,
+}
+
+/// The hash used for a JWK thumbprint, named as in RFC 9278.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThumbprintHash {
+    /// SHA-256, the hash used for the computed `kid`.
+    #[default]
+    Sha256,
+    /// SHA-384.
+    Sha384,
+    /// SHA-512.
+    Sha512,
+}
+
+impl ThumbprintHash {
+    /// The hash that produces a digest of the length in bytes.
+    fn from_digest_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(Self::Sha256),
+            48 => Some(Self::Sha384),
+            64 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// The RFC 9278 hash name, as used in a thumbprint URI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha-256",
+            Self::Sha384 => "sha-384",
+            Self::Sha512 => "sha-512",
+        }
+    }
+}
+
+/// A JWK Set.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JwkSet {
+    /// The keys in the set.
+    pub keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    /// The key in the set with the same public key material as the key, compared by their SHA-256
+    /// thumbprints so the `kid`, metadata, and private members may differ.
+    pub fn find(&self, key: &Jwk) -> Result<Option<&Jwk>, Error> {
+        let thumbprint = key.thumbprint(ThumbprintHash::Sha256)?;
+        for candidate in &self.keys {
+            if candidate.thumbprint(ThumbprintHash::Sha256)? == thumbprint {
+                return Ok(Some(candidate));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Jwk {
+    /// Start a [`JwkBuilder`] from the key to set its metadata members.
+    pub fn builder(self) -> JwkBuilder {
+        JwkBuilder::new(self)
+    }
+
+    /// Remove the private members, leaving only the public key.
+    ///
+    /// A symmetric key has no public part and is left unchanged.
+    pub fn strip_private(&mut self) {
+        match self {
+            Self::Ec(output) => output.d = None,
+            Self::Rsa(output) => {
+                output.d = None;
+                output.p = None;
+                output.q = None;
+                output.dp = None;
+                output.dq = None;
+                output.qi = None;
+            }
+            Self::Okp(output) => output.d = None,
+            Self::Oct(_) => {}
+        }
+    }
+
+    /// Replace the derived `alg`, EC and OKP keys only accept the algorithms for their curve, RSA
+    /// keys only accept the RSA algorithms, and symmetric keys only accept the HMAC algorithms. An
+    /// RSA key restricted to RSASSA-PSS only accepts the `PS*` algorithms.
+    pub fn set_alg(&mut self, alg: String) -> Result<(), Error> {
+        match self {
+            Self::Ec(output) => {
+                let curve = Curve::from_crv(&output.crv)
+                    .ok_or_else(|| Error::UnsupportedCurve(output.crv.clone()))?;
+                if !alg::validate_alg(&alg, curve) {
+                    return Err(Error::InvalidAlg { alg, kty: "EC" });
+                }
+                output.alg = alg;
+            }
+            Self::Rsa(output) => {
+                if !alg::validate_rsa_alg(&alg) || (output.is_pss && !alg.starts_with("PS")) {
+                    return Err(Error::InvalidAlg { alg, kty: "RSA" });
+                }
+                output.alg = alg;
+            }
+            Self::Okp(output) => {
+                if !alg::validate_okp_alg(&alg, &output.crv) {
+                    return Err(Error::InvalidAlg { alg, kty: "OKP" });
+                }
+                output.alg = alg;
+            }
+            Self::Oct(output) => {
+                if !alg::validate_oct_alg(&alg) {
+                    return Err(Error::InvalidAlg { alg, kty: "oct" });
+                }
+                output.alg = alg;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the `alg`, leaving the algorithm to be negotiated by the consumer.
+    pub fn clear_alg(&mut self) {
+        match self {
+            Self::Ec(output) => output.alg.clear(),
+            Self::Rsa(output) => output.alg.clear(),
+            Self::Okp(output) => output.alg.clear(),
+            Self::Oct(output) => output.alg.clear(),
+        }
+    }
+
+    /// Set the intended `use` of the key.
+    pub fn set_use(&mut self, use_: String) {
+        match self {
+            Self::Ec(output) => output.use_ = Some(use_),
+            Self::Rsa(output) => output.use_ = Some(use_),
+            Self::Okp(output) => output.use_ = Some(use_),
+            Self::Oct(output) => output.use_ = Some(use_),
+        }
+    }
+
+    /// Set the `use` implied by the `alg`, see [`alg::use_for_alg`], unless the key already has a
+    /// `use`.
+    pub fn infer_use(&mut self) {
+        let (alg, use_) = match self {
+            Self::Ec(output) => (&output.alg, &mut output.use_),
+            Self::Rsa(output) => (&output.alg, &mut output.use_),
+            Self::Okp(output) => (&output.alg, &mut output.use_),
+            Self::Oct(output) => (&output.alg, &mut output.use_),
+        };
+        if use_.is_none() {
+            *use_ = alg::use_for_alg(alg).map(str::to_string);
+        }
+    }
+
+    /// Set the permitted `key_ops` of the key.
+    pub fn set_key_ops(&mut self, key_ops: Vec<String>) {
+        match self {
+            Self::Ec(output) => output.key_ops = Some(key_ops),
+            Self::Rsa(output) => output.key_ops = Some(key_ops),
+            Self::Okp(output) => output.key_ops = Some(key_ops),
+            Self::Oct(output) => output.key_ops = Some(key_ops),
+        }
+    }
+
+    /// Set if the key is extractable, the WebCrypto `ext` member.
+    pub fn set_ext(&mut self, ext: bool) {
+        match self {
+            Self::Ec(output) => output.ext = Some(ext),
+            Self::Rsa(output) => output.ext = Some(ext),
+            Self::Okp(output) => output.ext = Some(ext),
+            Self::Oct(output) => output.ext = Some(ext),
+        }
+    }
+
+    /// Add the non-standard `point` member with the compressed public point of an EC key.
+    ///
+    /// The other key types have no point and are left unchanged.
+    pub fn include_point(&mut self) -> Result<(), Error> {
+        if let Self::Ec(output) = self {
+            output.point = Some(Base64UrlUnpadded::encode_string(
+                &output.compressed_point()?,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The coordinates of an EC key as hex, see [`EcOutput::coordinates_hex`].
+    ///
+    /// The other key types have no coordinates.
+    pub fn coordinates_hex(&self) -> Result<String, Error> {
+        match self {
+            Self::Ec(output) => output.coordinates_hex(),
+            Self::Rsa(_) => Err(Error::UnsupportedKeyType("RSA".to_string())),
+            Self::Okp(_) => Err(Error::UnsupportedKeyType("OKP".to_string())),
+            Self::Oct(_) => Err(Error::UnsupportedKeyType("oct".to_string())),
+        }
+    }
+
+    /// Add the non-standard `bits` member with the size of an RSA modulus or EC curve.
+    ///
+    /// The other key types are left unchanged.
+    pub fn include_bits(&mut self) -> Result<(), Error> {
+        let bits = self.rsa_bits()?;
+        match self {
+            Self::Ec(output) => {
+                let curve = Curve::from_crv(&output.crv)
+                    .ok_or_else(|| Error::UnsupportedCurve(output.crv.clone()))?;
+                output.bits = Some(curve.bits());
+            }
+            Self::Rsa(output) => output.bits = bits,
+            Self::Okp(_) | Self::Oct(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Set the `x5c` certificate chain of the key, the leaf certificate must come first. The
+    /// `x5t` and `x5t#S256` thumbprints are computed from the leaf certificate, and the `use` is
+    /// derived from its keyUsage or extKeyUsage extension.
+    ///
+    /// A symmetric key has no certificates and is left unchanged.
+    #[cfg(feature = "openssl")]
+    pub fn set_x5c(&mut self, x5c: Vec<String>) -> Result<(), Error> {
+        let (x5t, x5t_s256, use_) = match x5c.first() {
+            Some(leaf) => {
+                let der = decode_x5c_entry(leaf)?;
+                let (x5t, x5t_s256) = x5t(&der);
+                (Some(x5t), Some(x5t_s256), key_usage_use(&der))
+            }
+            None => (None, None, None),
+        };
+        if let Some(use_) = use_
+            && !matches!(self, Self::Oct(_))
+        {
+            self.set_use(use_.to_string());
+        }
+
+        match self {
+            Self::Ec(output) => {
+                output.x5c = Some(x5c);
+                output.x5t = x5t;
+                output.x5t_s256 = x5t_s256;
+            }
+            Self::Rsa(output) => {
+                output.x5c = Some(x5c);
+                output.x5t = x5t;
+                output.x5t_s256 = x5t_s256;
+            }
+            Self::Okp(output) => {
+                output.x5c = Some(x5c);
+                output.x5t = x5t;
+                output.x5t_s256 = x5t_s256;
+            }
+            Self::Oct(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// The `kid` of the key, if it has one.
+    pub fn kid(&self) -> Option<&str> {
+        match self {
+            Self::Ec(output) => output.kid.as_deref(),
+            Self::Rsa(output) => output.kid.as_deref(),
+            Self::Okp(output) => output.kid.as_deref(),
+            Self::Oct(output) => output.kid.as_deref(),
+        }
+    }
+
+    /// A short description of the key such as `EC P-256 public key, kid=..., alg=ES256`, private
+    /// keys are noted but their private members are never included.
+    pub fn summary(&self) -> Result<String, Error> {
+        let (kind, is_private, alg) = match self {
+            Self::Ec(output) => (
+                format!("EC {}", output.crv),
+                output.d.is_some(),
+                &output.alg,
+            ),
+            Self::Rsa(output) => (
+                format!("RSA {}-bit", self.rsa_bits()?.unwrap_or_default()),
+                output.d.is_some(),
+                &output.alg,
+            ),
+            Self::Okp(output) => (
+                format!("OKP {}", output.crv),
+                output.d.is_some(),
+                &output.alg,
+            ),
+            Self::Oct(output) => (
+                format!(
+                    "oct {}-bit",
+                    Zeroizing::new(decode_member(&output.k, "k")?).len() * 8
+                ),
+                true,
+                &output.alg,
+            ),
+        };
+
+        let mut summary = if is_private {
+            format!("{kind} key (private)")
+        } else {
+            format!("{kind} public key")
+        };
+        if let Some(kid) = self.kid() {
+            let _ = write!(summary, ", kid={kid}");
+        }
+        if !alg.is_empty() {
+            let _ = write!(summary, ", alg={alg}");
+        }
+
+        Ok(summary)
+    }
+
+    /// Compute the RFC 7638 thumbprint of the key with the hash.
+    pub fn thumbprint(&self, hash: ThumbprintHash) -> Result<String, Error> {
+        let members = match self {
+            Self::Ec(output) => BTreeMap::from([
+                ("crv", output.crv.as_str()),
+                ("kty", output.kty.as_str()),
+                ("x", output.x.as_str()),
+                ("y", output.y.as_str()),
+            ]),
+            Self::Rsa(output) => BTreeMap::from([
+                ("e", output.e.as_str()),
+                ("kty", output.kty.as_str()),
+                ("n", output.n.as_str()),
+            ]),
+            Self::Okp(output) => BTreeMap::from([
+                ("crv", output.crv.as_str()),
+                ("kty", output.kty.as_str()),
+                ("x", output.x.as_str()),
+            ]),
+            Self::Oct(output) => {
+                BTreeMap::from([("k", output.k.as_str()), ("kty", output.kty.as_str())])
+            }
+        };
+
+        hashed_thumbprint(members, hash)
+    }
+
+    /// Replace the computed `kid`.
+    pub fn set_kid(&mut self, kid: String) {
+        *self.kid_mut() = Some(kid);
+    }
+
+    /// Recompute the thumbprint `kid` with the hash, the computed `kid` uses SHA-256.
+    pub fn set_kid_hash(&mut self, hash: ThumbprintHash) -> Result<(), Error> {
+        let thumbprint = self.thumbprint(hash)?;
+        self.set_kid(thumbprint);
+
+        Ok(())
+    }
+
+    /// Re-encode the computed thumbprint `kid` in the format, or remove it.
+    pub fn set_kid_format(&mut self, format: KidFormat) -> Result<(), Error> {
+        let kid = self.kid_mut();
+        match format {
+            KidFormat::Thumbprint => {}
+            KidFormat::Hex => {
+                if let Some(thumbprint) = kid {
+                    let hash = decode_member(thumbprint, "kid")?;
+                    *thumbprint = hex(&hash);
+                }
+            }
+            KidFormat::ThumbprintUri => {
+                if let Some(thumbprint) = kid {
+                    let hash =
+                        ThumbprintHash::from_digest_len(decode_member(thumbprint, "kid")?.len())
+                            .unwrap_or_default();
+                    *thumbprint = format!(
+                        "urn:ietf:params:oauth:jwk-thumbprint:{}:{thumbprint}",
+                        hash.name()
+                    );
+                }
+            }
+            KidFormat::None => *kid = None,
+        }
+
+        Ok(())
+    }
+
+    fn kid_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Self::Ec(output) => &mut output.kid,
+            Self::Rsa(output) => &mut output.kid,
+            Self::Okp(output) => &mut output.kid,
+            Self::Oct(output) => &mut output.kid,
+        }
+    }
+
+    /// Check that the key is valid, an elliptic curve public point must lie on its curve and the
+    /// private members of an RSA key must agree with each other.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Self::Ec(output) => output.validate(),
+            Self::Rsa(output) => output.validate(),
+            Self::Okp(_) | Self::Oct(_) => Ok(()),
+        }
+    }
+
+    /// The size of the RSA modulus in bits, `None` for the other key types.
+    pub fn rsa_bits(&self) -> Result<Option<usize>, Error> {
+        let Self::Rsa(output) = self else {
+            return Ok(None);
+        };
+
+        let n = decode_member(&output.n, "n")?;
+        let bits = n
+            .first()
+            .map_or(0, |first| n.len() * 8 - first.leading_zeros() as usize);
+        Ok(Some(bits))
+    }
+
+    /// Check that an RSA modulus has at least `min_bits` bits, the other key types always pass.
+    pub fn check_rsa_bits(&self, min_bits: usize) -> Result<(), Error> {
+        match self.rsa_bits()? {
+            Some(bits) if bits < min_bits => Err(Error::WeakRsaKey { bits, min_bits }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the `alg` was derived from the key rather than defaulted. An RSA key that is not
+    /// restricted to RSASSA-PSS may be used with several algorithms, a symmetric key with any HMAC,
+    /// and a Brainpool key has no registered algorithm.
+    pub fn check_alg_derived(&self) -> Result<(), Error> {
+        match self {
+            Self::Ec(output) if output.alg.is_empty() => Err(Error::AmbiguousAlg("EC")),
+            Self::Rsa(output) if output.alg == "RS256" => Err(Error::AmbiguousAlg("RSA")),
+            Self::Oct(_) => Err(Error::AmbiguousAlg("oct")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reconstruct the key as a PEM, a PKCS#8 private key if the private members are present.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem(&self) -> Result<Vec<u8>, Error> {
+        self.to_pem_as(PemFormat::Pkcs8)
+    }
+
+    /// Reconstruct the key as a PEM in the format, a private key if the private members are
+    /// present.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem_as(&self, format: PemFormat) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Ec(output) => output.to_pem_as(format),
+            Self::Rsa(output) => output.to_pem_as(format),
+            Self::Okp(output) => output.to_pem(),
+            Self::Oct(_) => Err(Error::UnsupportedKeyType("oct".to_string())),
+        }
+    }
+}
+
+/// Implement `Display` as the pretty printed JSON and `FromStr` as parsing the JSON.
+macro_rules! impl_json {
+    ($($type:ty),+) => {$(
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+                f.write_str(&json)
+            }
+        }
+
+        impl FromStr for $type {
+            type Err = Error;
+
+            fn from_str(json: &str) -> Result<Self, Self::Err> {
+                serde_json::from_str(json).parse_err("JWK JSON")
+            }
+        }
+    )+};
+}
+
+impl_json!(Jwk, JwkSet, EcOutput, RsaOutput, OkpOutput, OctOutput);
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&PKeyRef<Public>> for Jwk {
+    type Error = Error;
+
+    fn try_from(key: &PKeyRef<Public>) -> Result<Self, Self::Error> {
+        debug!(key_type = key_type_name(key.id()), "detected public key");
+
+        if let Ok(ec_key) = key.ec_key() {
+            Ok(Self::Ec(EcOutput::try_from(ec_key.as_ref())?))
+        } else if let Ok(rsa_key) = key.rsa() {
+            let mut output = RsaOutput::try_from(rsa_key.as_ref())?;
+            if let Some(alg) = pss_alg(key) {
+                output.restrict_to_pss(alg);
+            }
+            Ok(Self::Rsa(output))
+        } else if matches!(key.id(), Id::ED25519 | Id::ED448 | Id::X25519 | Id::X448) {
+            Ok(Self::Okp(OkpOutput::try_from(key)?))
+        } else {
+            Err(Error::UnsupportedKeyType(key_type_name(key.id())))
+        }
+    }
+}
+#[cfg(feature = "openssl")]
+impl TryFrom<&PKeyRef<Private>> for Jwk {
+    type Error = Error;
+
+    fn try_from(key: &PKeyRef<Private>) -> Result<Self, Self::Error> {
+        debug!(key_type = key_type_name(key.id()), "detected private key");
+
+        if let Ok(ec_key) = key.ec_key() {
+            Ok(Self::Ec(EcOutput::try_from(ec_key.as_ref())?))
+        } else if let Ok(rsa_key) = key.rsa() {
+            let mut output = RsaOutput::try_from(rsa_key.as_ref())?;
+            if let Some(alg) = pss_alg(key) {
+                output.restrict_to_pss(alg);
+            }
+            Ok(Self::Rsa(output))
+        } else if matches!(key.id(), Id::ED25519 | Id::ED448 | Id::X25519 | Id::X448) {
+            Ok(Self::Okp(OkpOutput::try_from(key)?))
+        } else {
+            Err(Error::UnsupportedKeyType(key_type_name(key.id())))
+        }
+    }
+}
+
+/// The OpenSSL short name of the key type, falling back to its raw id.
+#[cfg(feature = "openssl")]
+fn key_type_name(id: Id) -> String {
+    Nid::from_raw(id.as_raw())
+        .short_name()
+        .map_or_else(|_| id.as_raw().to_string(), str::to_owned)
+}
+
+/// Convert a PEM encoded key to its JWK.
+///
+/// If the key cannot be parsed as the requested kind but can as the other kind,
+/// [`Error::UnexpectedKeyKind`] is returned instead of the parse error.
+pub fn pem_to_jwk(pem: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    if is_encrypted_pem(pem) {
+        return Err(if is_private {
+            Error::EncryptedKey
+        } else {
+            unexpected_key_kind(false)
+        });
+    }
+
+    match parse_pem(pem, is_private) {
+        Err(error) if !matches!(error, Error::Parse { .. } | Error::MissingPemKey) => Err(error),
+        Err(_) if parse_pem(pem, !is_private).is_ok() => Err(unexpected_key_kind(is_private)),
+        result => result,
+    }
+}
+
+/// The error for a key that is not of the requested kind.
+fn unexpected_key_kind(is_private: bool) -> Error {
+    let (expected, found) = if is_private {
+        ("private", "public")
+    } else {
+        ("public", "private")
+    };
+    Error::UnexpectedKeyKind { expected, found }
+}
+
+/// Parse the PEM key with OpenSSL.
+#[cfg(not(feature = "rustcrypto"))]
+fn parse_pem(pem: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    if is_private {
+        let key = PKey::private_key_from_pem(pem).parse_err("PEM")?;
+        Jwk::try_from(key.as_ref())
+    } else {
+        let key = PKey::public_key_from_pem(pem).parse_err("PEM")?;
+        Jwk::try_from(key.as_ref())
+    }
+}
+
+/// Convert a PEM encoded key to its pretty printed JWK JSON.
+pub fn pem_to_json(pem: &[u8], is_private: bool) -> Result<String, Error> {
+    let jwk = pem_to_jwk(pem, is_private)?;
+    serde_json::to_string_pretty(&jwk).map_err(Error::Serialize)
+}
+
+/// Convert an elliptic curve public key to its JWK as a JSON value, so extra members can be
+/// inserted.
+#[cfg(feature = "openssl")]
+pub fn ec_to_jwk_value(key: &EcKeyRef<Public>) -> Result<Value, Error> {
+    serde_json::to_value(EcOutput::try_from(key)?).map_err(Error::Serialize)
+}
+
+/// Convert an RSA public key to its JWK as a JSON value, so extra members can be inserted.
+#[cfg(feature = "openssl")]
+pub fn rsa_to_jwk_value(key: &RsaRef<Public>) -> Result<Value, Error> {
+    serde_json::to_value(RsaOutput::try_from(key)?).map_err(Error::Serialize)
+}
+
+/// Convert a PEM encoded key to its JWK, detecting if it is a private or public key.
+pub fn detect_pem_to_jwk(pem: &[u8]) -> Result<Jwk, Error> {
+    if is_encrypted_pem(pem) {
+        return Err(Error::EncryptedKey);
+    }
+
+    match parse_pem(pem, true) {
+        Ok(jwk) => Ok(jwk),
+        Err(_) => pem_to_jwk(pem, false),
+    }
+}
+
+/// Check that the JWK of a PEM encoded key reconstructs the same key, by comparing the DER
+/// encoded public key of the original and the key reconstructed from the JWK.
+///
+/// A JWK cannot carry the parameters of an RSASSA-PSS key, so such keys reconstruct as plain RSA
+/// keys and never match.
+#[cfg(feature = "openssl")]
+pub fn verify_pem(pem: &[u8]) -> Result<bool, Error> {
+    let jwk = detect_pem_to_jwk(pem)?;
+    let reconstructed = jwk.to_pem()?;
+
+    Ok(public_key_der(pem)? == public_key_der(&reconstructed)?)
+}
+
+/// The DER encoded public key of a PEM encoded private or public key.
+#[cfg(feature = "openssl")]
+fn public_key_der(pem: &[u8]) -> Result<Vec<u8>, Error> {
+    match PKey::private_key_from_pem(pem) {
+        Ok(key) => key.public_key_to_der(),
+        Err(_) => PKey::public_key_from_pem(pem)
+            .parse_err("PEM")?
+            .public_key_to_der(),
+    }
+    .backend_err("encode public key DER")
+}
+
+/// Convert an encrypted PEM encoded private key to its JWK.
+#[cfg(feature = "openssl")]
+pub fn encrypted_pem_to_jwk(pem: &[u8], passphrase: &[u8]) -> Result<Jwk, Error> {
+    let key = PKey::private_key_from_pem_passphrase(pem, passphrase).parse_err("encrypted PEM")?;
+    Jwk::try_from(key.as_ref())
+}
+
+/// Convert a DER encoded key to its JWK.
+///
+/// If the key cannot be parsed as the requested kind but can as the other kind,
+/// [`Error::UnexpectedKeyKind`] is returned instead of the parse error.
+pub fn der_to_jwk(der: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    match parse_der(der, is_private) {
+        Err(error) if !matches!(error, Error::Parse { .. }) => Err(error),
+        Err(_) if parse_der(der, !is_private).is_ok() => Err(unexpected_key_kind(is_private)),
+        result => result,
+    }
+}
+
+/// Parse the DER key with OpenSSL.
+#[cfg(not(feature = "rustcrypto"))]
+fn parse_der(der: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    if is_private {
+        let key = PKey::private_key_from_der(der).parse_err("DER")?;
+        Jwk::try_from(key.as_ref())
+    } else {
+        let key = PKey::public_key_from_der(der).parse_err("DER")?;
+        Jwk::try_from(key.as_ref())
+    }
+}
+
+/// Convert a DER encoded key to its JWK, detecting if it is a private or public key.
+pub fn detect_der_to_jwk(der: &[u8]) -> Result<Jwk, Error> {
+    match parse_der(der, true) {
+        Ok(jwk) => Ok(jwk),
+        Err(_) => der_to_jwk(der, false),
+    }
+}
+
+/// Convert the private key of a DER encoded PKCS#12 archive to its JWK.
+#[cfg(feature = "openssl")]
+pub fn pkcs12_to_jwk(der: &[u8], passphrase: &str) -> Result<Jwk, Error> {
+    let archive = Pkcs12::from_der(der).parse_err("PKCS#12")?;
+    let parsed = archive.parse2(passphrase).backend_err("decrypt PKCS#12")?;
+
+    let key = parsed.pkey.ok_or(Error::MissingPrivateKey)?;
+    let mut jwk = Jwk::try_from(key.as_ref())?;
+
+    // The leaf certificate must come first in the chain.
+    let x5c = parsed
+        .cert
+        .iter()
+        .map(|certificate| x5c_entry(certificate))
+        .chain(parsed.ca.iter().flatten().map(x5c_entry))
+        .collect::<Result<Vec<String>, Error>>()?;
+    if !x5c.is_empty() {
+        jwk.set_x5c(x5c)?;
+    }
+
+    Ok(jwk)
+}
+
+/// Convert a raw OKP public or private key, as written by libsodium style tooling, to its JWK.
+pub fn raw_okp_to_jwk(raw: &[u8], crv: &str, is_private: bool) -> Result<Jwk, Error> {
+    let expected = key_length(crv)?;
+    if raw.len() != expected {
+        return Err(Error::InvalidKeyLength {
+            crv: crv.to_string(),
+            expected,
+            length: raw.len(),
+        });
+    }
+
+    parse_raw_okp(raw, crv, is_private)
+}
+
+/// Parse the raw OKP key with OpenSSL.
+#[cfg(feature = "openssl")]
+fn parse_raw_okp(raw: &[u8], crv: &str, is_private: bool) -> Result<Jwk, Error> {
+    let id = okp_id(crv)?;
+    if is_private {
+        let key = PKey::private_key_from_raw_bytes(raw, id).parse_err("raw private key")?;
+        Jwk::try_from(key.as_ref())
+    } else {
+        let key = PKey::public_key_from_raw_bytes(raw, id).parse_err("raw public key")?;
+        Jwk::try_from(key.as_ref())
+    }
+}
+
+/// Convert a raw symmetric secret to its JWK.
+pub fn secret_to_jwk(secret: &[u8]) -> Result<Jwk, Error> {
+    Ok(Jwk::Oct(OctOutput::try_from(secret)?))
+}
+
+/// Decode a base64url encoded JWK member.
+pub(crate) fn decode_member(value: &str, member: &str) -> Result<Vec<u8>, Error> {
+    Base64UrlUnpadded::decode_vec(value).map_err(|source| Error::Encoding {
+        name: format!("`{member}` member"),
+        source,
+    })
+}
+
+/// Encode the bytes as lowercase hex.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// Decode a base64url encoded JWK member as a big number.
+#[cfg(feature = "openssl")]
+pub(crate) fn decode_big_num(value: &str, member: &str) -> Result<BigNum, Error> {
+    BigNum::from_slice(&Zeroizing::new(decode_member(value, member)?))
+        .backend_err("convert member to a big number")
+}
+
+/// Compute the RFC 7638 thumbprint of a JWK from its required public members.
+pub(crate) fn thumbprint(members: BTreeMap<&str, &str>) -> Result<String, Error> {
+    hashed_thumbprint(members, ThumbprintHash::Sha256)
+}
+
+/// Compute the RFC 7638 thumbprint of a JWK from its required public members with the hash.
+fn hashed_thumbprint(members: BTreeMap<&str, &str>, hash: ThumbprintHash) -> Result<String, Error> {
+    // A BTreeMap serializes with its members in lexicographic order and serde_json adds no
+    // whitespace, which is exactly the canonical form the RFC requires.
+    let json = serde_json::to_string(&members).map_err(Error::Serialize)?;
+    #[cfg(feature = "openssl")]
+    let digest = match hash {
+        ThumbprintHash::Sha256 => sha256(json.as_bytes()).to_vec(),
+        ThumbprintHash::Sha384 => sha384(json.as_bytes()).to_vec(),
+        ThumbprintHash::Sha512 => sha512(json.as_bytes()).to_vec(),
+    };
+    #[cfg(not(feature = "openssl"))]
+    let digest = digest(hash, json.as_bytes());
+
+    Ok(Base64UrlUnpadded::encode_string(&digest))
+}
@@ -1,17 +1,35 @@
-use std::{fs, path::PathBuf};
-
-use base64ct::{Base64UrlUnpadded, Encoding};
-use clap::{Parser, Subcommand};
-use openssl::{
-    bn::{BigNum, BigNumContext},
-    ec::{EcKey, EcKeyRef, PointConversionForm},
-    nid::Nid,
-    pkey::{PKey, Private, Public},
-    rsa::{Rsa, RsaRef},
-    sha::sha256,
+#[cfg(feature = "openssl")]
+use std::sync::{Mutex, PoisonError};
+use std::{
+    collections::HashSet,
+    env,
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
-use serde::{Deserialize, Serialize};
-use ts_rust_helper::error::{IntoErrorReport, Report, ReportResult, ReportStyle};
+
+use base64ct::{Base64, Encoding};
+use clap::{Args, Parser, Subcommand, ValueEnum, builder::PossibleValuesParser};
+use pem_params::{
+    ALGORITHMS, Error, Jwk, JwkSet, KEY_OPERATIONS, SortedJwk, SortedJwkSet, ThumbprintHash,
+    der_to_jwk, detect_der_to_jwk, detect_pem_to_jwk, pem_to_jwk, raw_okp_to_jwk, schema,
+    secret_to_jwk, split_pem, ssh_to_jwk,
+};
+#[cfg(feature = "openssl")]
+use pem_params::{
+    KeySpec, attach_certificates, certificate_der_to_jwk, certificate_pem_to_jwk,
+    encrypted_pem_to_jwk, generate, pkcs12_to_jwk, verify_pem, x5c_from_pem,
+};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use serde_json::{Serializer, Value, json, ser::PrettyFormatter};
+use tracing::{Level, debug};
+use ts_rust_helper::error::{IntoErrorReport, ReportResult, ReportStyle};
+use zeroize::Zeroizing;
 
 #[derive(Debug, Parser)]
 #[command(name = "pem-params")]
@@ -20,177 +38,1481 @@ struct Cli {
     /// The PEM key type
     #[clap(subcommand)]
     pub key_type: KeyType,
+
+    /// Wrap the output in a JWK Set.
+    #[clap(long, global = true)]
+    pub jwks: bool,
+
+    /// Output each key as single line JSON on its own line instead of a JWK Set.
+    #[clap(long, global = true, conflicts_with = "jwks")]
+    pub ndjson: bool,
+
+    /// The encoding of the key file.
+    #[clap(long, global = true, value_enum, default_value_t = InputFormat::Pem)]
+    pub input_format: InputFormat,
+
+    /// The intended use of the key.
+    #[clap(long = "use", global = true, env = "ECJWK_USE", value_parser = PossibleValuesParser::new(["sig", "enc"]))]
+    pub use_: Option<String>,
+
+    /// Set the `use` from the `alg` when the key has none: `sig` for the signature algorithms, and
+    /// `enc` for `ECDH-ES`, `RSA-OAEP`, and `RSA1_5`.
+    #[clap(long, global = true)]
+    pub infer_use: bool,
+
+    /// The operations the key is intended for, may be repeated.
+    #[clap(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(KEY_OPERATIONS)
+    )]
+    pub key_ops: Vec<String>,
+
+    /// Add the WebCrypto `ext` member, if the key is extractable.
+    #[clap(long, global = true)]
+    pub ext: Option<bool>,
+
+    /// Add a non-standard `point` member to EC keys with the base64url compressed SEC1 point, for
+    /// debugging and tools that want the raw point. JWK consumers will not understand it.
+    #[clap(long, global = true)]
+    pub include_point: bool,
+
+    /// Add a non-standard `bits` member to RSA and EC keys with the size of the modulus or curve.
+    /// JWK consumers will not understand it.
+    #[clap(long, global = true)]
+    pub include_bits: bool,
+
+    /// Certificate PEMs to include as the `x5c` chain, leaf first, may be repeated.
+    #[cfg(feature = "openssl")]
+    #[clap(long, global = true)]
+    pub x5c: Vec<PathBuf>,
+
+    /// How the `kid` is produced.
+    #[clap(
+        long,
+        global = true,
+        env = "ECJWK_KID_FORMAT",
+        value_enum,
+        default_value_t = KidFormat::Thumbprint
+    )]
+    pub kid_format: KidFormat,
+
+    /// The hash of the thumbprint `kid`.
+    #[clap(long, global = true, value_enum, default_value_t = Digest::Sha256)]
+    pub kid_hash: Digest,
+
+    /// Use this `kid` instead of the computed thumbprint.
+    #[clap(long, global = true)]
+    pub kid: Option<String>,
+
+    /// Use the RFC 9278 thumbprint URI as the `kid`, as published by some identity providers, this
+    /// overrides `--kid-format`.
+    #[clap(long, global = true, conflicts_with = "kid")]
+    pub kid_uri: bool,
+
+    /// Skip checking that elliptic curve public points lie on their curve and that the private
+    /// members of RSA keys agree with each other.
+    #[clap(long, global = true)]
+    pub no_validate: bool,
+
+    /// Fail instead of converting RSA keys below 2048 bits or keys whose `alg` would be guessed.
+    #[clap(long, global = true)]
+    pub strict: bool,
+
+    /// Fail instead of converting RSA keys with a modulus smaller than this many bits.
+    #[clap(long, global = true)]
+    pub min_rsa_bits: Option<usize>,
+
+    /// Only output the public members, even for a private key.
+    #[clap(long, global = true)]
+    pub public_only: bool,
+
+    /// Use this `alg` instead of deriving it from the key.
+    #[clap(long, global = true, env = "ECJWK_ALG", value_parser = PossibleValuesParser::new(ALGORITHMS))]
+    pub alg: Option<String>,
+
+    /// Omit the `alg`, for keys whose algorithm is negotiated elsewhere, this overrides `--alg`.
+    #[clap(long, global = true)]
+    pub no_alg: bool,
+
+    /// Output the JWK members in a fixed canonical order instead of the declaration order.
+    #[clap(long, global = true)]
+    pub sort_keys: bool,
+
+    /// The format of the output.
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+
+    /// Suppress warnings such as skipped files, errors are still printed.
+    #[clap(long, short, global = true)]
+    pub quiet: bool,
+
+    /// Log each step of the conversion to stderr.
+    #[clap(long, short, global = true)]
+    pub verbose: bool,
+
+    /// Never colour error reports, they are only coloured when stderr is a terminal and `NO_COLOR`
+    /// is not set.
+    #[clap(long, global = true)]
+    pub no_color: bool,
+
+    /// Print errors to stderr as a single line JSON object with the failed operation as `context`
+    /// and the causes as `error`, instead of a report.
+    #[clap(long, global = true)]
+    pub pretty_stderr: bool,
+
+    /// Output single line JSON instead of pretty printing it, the same as `--indent 0`.
+    #[clap(long, global = true)]
+    pub compact: bool,
+
+    /// Convert a private key given to `public`, or a public key given to `private`, with a warning
+    /// instead of failing.
+    #[clap(long, global = true)]
+    pub lenient: bool,
+
+    /// Print a short description of each key instead of its JWK.
+    #[clap(long, global = true)]
+    pub summary: bool,
+
+    /// Print the `x`, `y`, and `d` of each EC key as hex padded to the field size instead of its
+    /// JWK, for comparing against other tools.
+    #[clap(long, global = true, conflicts_with = "summary")]
+    pub coords_hex: bool,
+
+    /// The number of spaces to indent pretty printed JSON with.
+    #[clap(long, global = true, default_value_t = 2)]
+    pub indent: usize,
+
+    #[clap(flatten)]
+    pub output: OutputArgs,
+}
+
+/// The OKP curves a raw key may be on.
+const OKP_CURVES: [&str; 4] = ["Ed25519", "Ed448", "X25519", "X448"];
+
+/// If error reports are coloured, decided once the arguments are parsed.
+static COLOURED: AtomicBool = AtomicBool::new(false);
+
+/// If files are only previewed instead of written, decided once the arguments are parsed.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// The number of files a dry run would have written.
+static DRY_RUN_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// If errors are printed as JSON, decided once the arguments are parsed.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// If a key of the wrong kind for the subcommand is converted anyway, decided once the arguments
+/// are parsed.
+static LENIENT: AtomicBool = AtomicBool::new(false);
+
+/// Held while prompting for a passphrase, so parallel conversions do not prompt at once.
+#[cfg(feature = "openssl")]
+static PROMPT: Mutex<()> = Mutex::new(());
+
+/// If non-fatal warnings are suppressed, decided once the arguments are parsed.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// The exit code when the input has no complete PEM block, other errors exit with 1.
+const NO_PEM_EXIT_CODE: i32 = 3;
+
+/// The input of a PEM conversion has no complete PEM block, reported with [`NO_PEM_EXIT_CODE`] so
+/// automation can tell it apart from a key that fails to parse.
+#[derive(Debug, thiserror::Error)]
+enum PemInputError {
+    /// There is no `-----BEGIN` line, such as an empty file.
+    #[error("no PEM block found in {}", .0.display())]
+    Missing(PathBuf),
+    /// The only block has no `-----END` line.
+    #[error("the PEM block in {} is truncated", .0.display())]
+    Truncated(PathBuf),
+}
+
+/// Print a non-fatal warning to stderr unless warnings are suppressed.
+fn warn(message: impl Display) {
+    if !QUIET.load(Ordering::Relaxed) {
+        // Reports end with a newline of their own.
+        eprintln!("Warning: {}", message.to_string().trim_end());
+    }
+}
+
+/// The style of error reports.
+fn report_style() -> ReportStyle {
+    if COLOURED.load(Ordering::Relaxed) {
+        ReportStyle::Coloured
+    } else {
+        ReportStyle::Basic
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum InputFormat {
+    Pem,
+    Der,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Digest {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl From<Digest> for ThumbprintHash {
+    fn from(digest: Digest) -> Self {
+        match digest {
+            Digest::Sha256 => Self::Sha256,
+            Digest::Sha384 => Self::Sha384,
+            Digest::Sha512 => Self::Sha512,
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum PemFormat {
+    /// PKCS#8 private keys and SubjectPublicKeyInfo public keys.
+    Pkcs8,
+    /// SEC1 EC private keys and PKCS#1 RSA keys, other keys are PKCS#8.
+    Traditional,
+}
+
+#[cfg(feature = "openssl")]
+impl From<PemFormat> for pem_params::PemFormat {
+    fn from(format: PemFormat) -> Self {
+        match format {
+            PemFormat::Pkcs8 => Self::Pkcs8,
+            PemFormat::Traditional => Self::Traditional,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaLanguage {
+    JsonSchema,
+    Typescript,
+}
+
+#[cfg(feature = "openssl")]
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Kty {
+    Ec,
+    Rsa,
+    Okp,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum KidFormat {
+    /// The RFC 7638 base64url thumbprint.
+    Thumbprint,
+    /// The RFC 7638 thumbprint as a lowercase hex digest of the `--kid-hash`.
+    #[value(alias = "hex-sha256")]
+    Hex,
+    /// Omit the `kid`.
+    None,
+}
+
+impl From<KidFormat> for pem_params::KidFormat {
+    fn from(format: KidFormat) -> Self {
+        match format {
+            KidFormat::Thumbprint => Self::Thumbprint,
+            KidFormat::Hex => Self::Hex,
+            KidFormat::None => Self::None,
+        }
+    }
 }
 
 #[derive(Debug, Subcommand)]
 enum KeyType {
     Private {
-        /// Path to the private key PEM.
-        key: PathBuf,
+        #[clap(flatten)]
+        key: KeyArgs,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+
+        /// Read the key as the raw private key bytes of an OKP key on this curve.
+        #[clap(long, value_name = "CRV", value_parser = PossibleValuesParser::new(OKP_CURVES))]
+        raw_okp: Option<String>,
     },
     Public {
-        /// Path to the public key PEM.
+        #[clap(flatten)]
+        key: KeyArgs,
+
+        /// Read the key as the raw public key bytes of an OKP key on this curve.
+        #[clap(long, value_name = "CRV", value_parser = PossibleValuesParser::new(OKP_CURVES))]
+        raw_okp: Option<String>,
+    },
+    /// Detect if the key is private or public.
+    Auto {
+        #[clap(flatten)]
+        key: KeyArgs,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    #[cfg(feature = "openssl")]
+    Certificate {
+        /// Path to the X.509 certificate PEM, or `-` to read from stdin.
+        certificate: PathBuf,
+    },
+    /// Convert the private key of a PKCS#12 archive.
+    #[cfg(feature = "openssl")]
+    Pkcs12 {
+        /// Path to the PKCS#12 archive, or `-` to read from stdin.
+        path: PathBuf,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Convert an OpenSSH public key.
+    Ssh {
+        /// Path to the OpenSSH public key, or `-` to read from stdin.
         key: PathBuf,
     },
+    /// Convert a raw symmetric secret to an `oct` JWK.
+    Oct {
+        /// Path to the raw secret, or `-` to read from stdin.
+        key: PathBuf,
+    },
+    /// Convert several key files into a JWK Set, in the order they are given.
+    Combine {
+        /// Paths to the key PEMs.
+        #[clap(required = true)]
+        keys: Vec<PathBuf>,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Add a key to an existing JWK Set file, or remove keys from it, and write it back.
+    Merge {
+        /// Path to the key file to add, keys whose `kid` is already in the set are skipped.
+        key: Option<PathBuf>,
+
+        /// Path to the JWK Set file to merge into, it is written back unless `--output` is given.
+        #[clap(long, value_name = "JWKS_PATH")]
+        into: PathBuf,
+
+        /// Remove the key with this `kid` from the set, may be repeated.
+        #[clap(long, value_name = "KID")]
+        remove_kid: Vec<String>,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Convert every key file in a directory into a JWK Set.
+    Batch {
+        /// Path to the directory of key files.
+        dir: PathBuf,
+
+        /// Also convert the `.pem`, `.key`, and `.crt` files in subdirectories.
+        #[clap(long)]
+        recursive: bool,
+
+        /// Write each key to `<DIR>/<kid>.json` instead of outputting a JWK Set, keys without a
+        /// usable `kid` are named by their position.
+        #[clap(long, value_name = "DIR")]
+        split_out: Option<PathBuf>,
+    },
+    /// Generate a fresh private key and output its JWK.
+    #[cfg(feature = "openssl")]
+    Generate {
+        /// The type of key to generate.
+        #[clap(long, value_enum, default_value_t = Kty::Ec)]
+        kty: Kty,
+
+        /// The curve of an EC or OKP key, defaults to `P-256` or `Ed25519`.
+        #[clap(long, value_parser = PossibleValuesParser::new([
+            "P-256", "P-384", "P-521", "secp256k1", "BP-256", "BP-384", "BP-512", "Ed25519",
+            "Ed448", "X25519", "X448",
+        ]))]
+        curve: Option<String>,
+
+        /// The modulus size of an RSA key.
+        #[clap(long, default_value_t = 2048)]
+        rsa_bits: u32,
+
+        /// Also write the private key PEM to this file.
+        #[clap(long)]
+        pem_out: Option<PathBuf>,
+    },
+    /// Output only the RFC 7638 thumbprint of each key.
+    Thumbprint {
+        /// Path to the key PEM, or `-` to read from stdin.
+        key: PathBuf,
+
+        /// The hash of the thumbprint. This is `--digest` rather than `--alg` because the global
+        /// `--alg` already sets the JWK `alg` of every subcommand.
+        #[clap(long, value_enum, default_value_t = Digest::Sha256)]
+        digest: Digest,
+    },
+    /// Check that the key converts to a JWK that reconstructs the same key.
+    #[cfg(feature = "openssl")]
+    Verify {
+        /// Path to the key PEM, or `-` to read from stdin.
+        key: PathBuf,
+    },
+    /// Check that each key in the key file is in a JWK Set, printing the `kid` of the matching key
+    /// and exiting with 1 if any key is missing.
+    Contains {
+        /// Path to the key file, or `-` to read from stdin.
+        key: PathBuf,
+
+        /// Path to the JWK Set JSON.
+        #[clap(long, value_name = "JWKS_PATH")]
+        jwks_file: PathBuf,
+
+        #[clap(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Convert a JWK back to a PEM.
+    #[cfg(feature = "openssl")]
+    JwkToPem {
+        /// Path to the JWK JSON, or `-` to read from stdin.
+        jwk: PathBuf,
+    },
+    /// Convert each key of a JWK Set back to a PEM, written to `<DIR>/<kid>.pem`.
+    #[cfg(feature = "openssl")]
+    JwksToPem {
+        /// Path to the JWK Set JSON, or `-` to read from stdin.
+        path: PathBuf,
+
+        /// The directory the PEMs are written to, keys without a usable `kid` are named by their
+        /// position.
+        #[clap(long, value_name = "DIR")]
+        out_dir: PathBuf,
+
+        /// The encoding of the PEMs.
+        #[clap(long, value_enum, default_value_t = PemFormat::Pkcs8)]
+        pem_format: PemFormat,
+    },
+    /// Output the shape of the JWK output, for keeping client types in sync.
+    #[clap(hide = true)]
+    Schema {
+        /// The language of the schema.
+        #[clap(value_enum, default_value_t = SchemaLanguage::JsonSchema)]
+        language: SchemaLanguage,
+    },
+}
+
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct KeyArgs {
+    /// Path to the key PEM, or `-` to read from stdin.
+    key: Option<PathBuf>,
+
+    /// The base64 encoded DER key, instead of a path.
+    #[clap(long)]
+    key_b64: Option<String>,
+}
+
+impl KeyArgs {
+    /// If the key is read from stdin.
+    fn is_stdin(&self) -> bool {
+        self.key_b64.is_none() && self.key.as_deref().is_some_and(is_stdin)
+    }
+
+    /// Convert the keys from the path or the base64 encoded DER.
+    fn convert(
+        self,
+        conversion: &Conversion,
+        format: InputFormat,
+    ) -> ReportResult<'static, (Vec<Jwk>, bool)> {
+        match (self.key, self.key_b64) {
+            (_, Some(key_b64)) => {
+                let der = Zeroizing::new(
+                    Base64::decode_vec(Zeroizing::new(key_b64).trim())
+                        .into_report(report_style(), "decode base64 key")?,
+                );
+                Ok((vec![conversion.convert(InputFormat::Der, &der)?], false))
+            }
+            (key, None) => convert_file(&key.unwrap_or_default(), conversion, format),
+        }
+    }
 }
 
-fn main() -> ReportResult<'static, ()> {
+#[derive(Debug, Args)]
+struct PassphraseArgs {
+    /// The passphrase for the encrypted private key. If more than one source is given,
+    /// `--passphrase-file` is used over `--passphrase-env`, which is used over `--passphrase`.
+    #[clap(long)]
+    passphrase: Option<String>,
+
+    /// The environment variable containing the passphrase for the encrypted private key, if no
+    /// passphrase source is provided the passphrase is prompted for on a TTY.
+    #[clap(long)]
+    passphrase_env: Option<String>,
+
+    /// The file whose first line is the passphrase for the encrypted private key, such as a
+    /// mounted secret.
+    #[clap(long, value_name = "PATH")]
+    passphrase_file: Option<PathBuf>,
+}
+
+impl PassphraseArgs {
+    /// Resolve the passphrase from the provided sources, if any, the file is used over the
+    /// environment variable, which is used over the flag. The passphrase file cannot be stdin when
+    /// the key is read from stdin.
+    fn resolve(self, key_is_stdin: bool) -> ReportResult<'static, Option<String>> {
+        if let Some(path) = self.passphrase_file {
+            if key_is_stdin && is_stdin(&path) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the key is read from stdin, so the passphrase file cannot be as well",
+                ))
+                .into_report(report_style(), "read passphrase file");
+            }
+
+            let contents = String::from_utf8(read_input(&path, "passphrase")?)
+                .into_report(report_style(), "read passphrase file")?;
+            // Only the line ending is trimmed, other whitespace may be part of the passphrase.
+            let line = contents.split('\n').next().unwrap_or_default();
+            return Ok(Some(line.strip_suffix('\r').unwrap_or(line).to_string()));
+        }
+
+        match self.passphrase_env {
+            Some(variable) => env::var(&variable)
+                .map(Some)
+                .into_report(report_style(), format!("read passphrase from {variable}")),
+            None => Ok(self.passphrase),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct OutputArgs {
+    /// Write the output to this file instead of stdout.
+    #[clap(long, short, global = true)]
+    output: Option<PathBuf>,
+
+    /// Overwrite output files if they already exist.
+    #[clap(long, global = true)]
+    force: bool,
+
+    /// Print the files that would be written, and then how many, instead of writing them.
+    #[clap(long, global = true)]
+    dry_run: bool,
+
+    /// Omit the newline at the end of the output, for tools that embed it byte for byte.
+    #[clap(long, global = true)]
+    no_trailing_newline: bool,
+}
+
+impl OutputArgs {
+    /// Write the contents to the output file, or to stdout if no file was provided.
+    fn write(&self, contents: &[u8]) -> ReportResult<'static, ()> {
+        let contents = self.trim_newline(contents);
+
+        match &self.output {
+            Some(path) => self.write_file(path, contents),
+            None => io::stdout()
+                .write_all(contents)
+                .into_report(report_style(), "write output"),
+        }
+    }
+
+    /// The contents without their trailing newline, if it should be omitted.
+    fn trim_newline<'a>(&self, contents: &'a [u8]) -> &'a [u8] {
+        match contents {
+            [contents @ .., b'\n'] if self.no_trailing_newline => contents,
+            contents => contents,
+        }
+    }
+
+    /// Write the contents to the file, refusing to overwrite it unless forced.
+    fn write_file(&self, path: &Path, contents: &[u8]) -> ReportResult<'static, ()> {
+        if !self.force && path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists, use --force to overwrite it",
+                    path.display()
+                ),
+            ))
+            .into_report(report_style(), "write output file");
+        }
+        self.replace_file(path, contents)
+    }
+
+    /// Write the contents to the file, replacing it if it exists.
+    fn replace_file(&self, path: &Path, contents: &[u8]) -> ReportResult<'static, ()> {
+        if self.dry_run {
+            eprintln!("Would write {}", path.display());
+            DRY_RUN_FILES.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        // Write to a temporary file beside the output and rename it into place, so a reader never
+        // sees a partially written file.
+        let mut temp_name = OsString::from(".");
+        temp_name.push(path.file_name().unwrap_or_default());
+        temp_name.push(format!(".{}.tmp", process::id()));
+        let temp_path = path.with_file_name(temp_name);
+
+        write_temp_file(&temp_path, path, contents)
+            .into_report(report_style(), "write output file")?;
+        let result = fs::rename(&temp_path, path);
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result.into_report(report_style(), "write output file")
+    }
+}
+
+/// Write the contents to a new temporary file, readable only by the owner unless the file it
+/// replaces had other permissions, and flush it to disk.
+fn write_temp_file(temp_path: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    // Never follow or overwrite anything already at the temporary path, and only clean up the
+    // file once it is ours.
+    let mut file = options.open(temp_path)?;
+    let result = file.write_all(contents).and_then(|()| {
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(temp_path);
+    }
+    result
+}
+
+fn main() {
+    let Err(report) = run() else {
+        if DRY_RUN.load(Ordering::Relaxed) {
+            let count = DRY_RUN_FILES.load(Ordering::Relaxed);
+            eprintln!(
+                "Dry run, {count} file{} would be written",
+                if count == 1 { "" } else { "s" }
+            );
+        }
+        return;
+    };
+
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        let mut causes = vec![report.error.to_string()];
+        let mut source = report.error.source();
+        while let Some(error) = source {
+            causes.push(error.to_string());
+            source = error.source();
+        }
+        let error = json!({ "error": causes.join(": "), "context": report.operation });
+        eprintln!("{error}");
+    } else {
+        eprintln!("Error: {report:?}");
+    }
+
+    if report.error.is::<PemInputError>() {
+        process::exit(NO_PEM_EXIT_CODE);
+    }
+    process::exit(1);
+}
+
+fn run() -> ReportResult<'static, ()> {
     let cli = Cli::parse();
 
-    match cli.key_type {
-        KeyType::Private { key } => {
-            let pem = fs::read(key).into_report(ReportStyle::Coloured, "read PEM file")?;
-            let key =
-                PKey::private_key_from_pem(&pem).into_report(ReportStyle::Coloured, "parse PEM")?;
-
-            if let Ok(ec_key) = key.ec_key() {
-                unimplemented!("The key id {:?} is not implemented", key.id())
-            } else if let Ok(rsa_key) = key.rsa() {
-                let output = RsaOutput::try_from(rsa_key.as_ref())?;
-                let json = serde_json::to_string_pretty(&output)
-                    .into_report(ReportStyle::Coloured, "serialize output")?;
-                println!("{json}");
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    COLOURED.store(
+        !cli.no_color && !no_color && io::stderr().is_terminal(),
+        Ordering::Relaxed,
+    );
+    DRY_RUN.store(cli.output.dry_run, Ordering::Relaxed);
+    JSON_ERRORS.store(cli.pretty_stderr, Ordering::Relaxed);
+    LENIENT.store(cli.lenient, Ordering::Relaxed);
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+    if cli.verbose {
+        tracing_subscriber::fmt()
+            .with_max_level(Level::DEBUG)
+            .with_writer(io::stderr)
+            .with_ansi(COLOURED.load(Ordering::Relaxed))
+            .init();
+    }
+
+    let mut split_out = None;
+    let mut merge_into = None;
+    let (mut keys, is_set) = match cli.key_type {
+        KeyType::Private {
+            key,
+            raw_okp: Some(crv),
+            ..
+        } => key.convert(&Conversion::RawOkp(crv, true), cli.input_format)?,
+        KeyType::Private {
+            key, passphrase, ..
+        } => {
+            let conversion = Conversion::Private(passphrase.resolve(key.is_stdin())?);
+            key.convert(&conversion, cli.input_format)?
+        }
+        KeyType::Public {
+            key,
+            raw_okp: Some(crv),
+        } => key.convert(&Conversion::RawOkp(crv, false), cli.input_format)?,
+        KeyType::Public { key, .. } => key.convert(&Conversion::Public, cli.input_format)?,
+        KeyType::Auto { key, passphrase } => {
+            let conversion = Conversion::Auto(passphrase.resolve(key.is_stdin())?);
+            key.convert(&conversion, cli.input_format)?
+        }
+        #[cfg(feature = "openssl")]
+        KeyType::Certificate { certificate } => {
+            convert_file(&certificate, &Conversion::Certificate, cli.input_format)?
+        }
+        #[cfg(feature = "openssl")]
+        KeyType::Pkcs12 { path, passphrase } => convert_file(
+            &path,
+            &Conversion::Pkcs12(passphrase.resolve(is_stdin(&path))?.unwrap_or_default()),
+            cli.input_format,
+        )?,
+        KeyType::Ssh { key } => convert_file(&key, &Conversion::Ssh, cli.input_format)?,
+        KeyType::Oct { key } => convert_file(&key, &Conversion::Oct, cli.input_format)?,
+        #[cfg(feature = "openssl")]
+        KeyType::Generate {
+            kty,
+            curve,
+            rsa_bits,
+            pem_out,
+        } => {
+            let spec = match kty {
+                Kty::Ec => KeySpec::Ec(curve.as_deref().unwrap_or("P-256")),
+                Kty::Rsa => KeySpec::Rsa(rsa_bits),
+                Kty::Okp => KeySpec::Okp(curve.as_deref().unwrap_or("Ed25519")),
+            };
+            let (jwk, pem) = generate(spec).into_report(report_style(), "generate key")?;
+            if let Some(path) = &pem_out {
+                cli.output.write_file(path, &pem)?;
+            }
+
+            (vec![jwk], false)
+        }
+        KeyType::Combine { keys, passphrase } => {
+            let key_is_stdin = keys.iter().any(|key| is_stdin(key));
+            let conversion = Conversion::Auto(passphrase.resolve(key_is_stdin)?);
+            let mut combined = Vec::new();
+            for key in &keys {
+                combined.extend(convert_file(key, &conversion, cli.input_format)?.0);
+            }
+
+            (combined, true)
+        }
+        KeyType::Merge {
+            key,
+            into,
+            remove_kid,
+            passphrase,
+        } => {
+            let keys = match key {
+                Some(key) => {
+                    let conversion = Conversion::Auto(passphrase.resolve(is_stdin(&key))?);
+                    convert_file(&key, &conversion, cli.input_format)?.0
+                }
+                None => Vec::new(),
+            };
+            merge_into = Some((into, remove_kid));
+
+            (keys, true)
+        }
+        KeyType::Batch {
+            dir,
+            recursive,
+            split_out: dir_out,
+        } => {
+            split_out = dir_out;
+            (convert_directory(&dir, recursive, cli.input_format)?, true)
+        }
+        KeyType::Thumbprint { key, digest } => {
+            let (keys, _) = convert_file(&key, &Conversion::Auto(None), cli.input_format)?;
+            let mut output = String::new();
+            for key in keys {
+                output.push_str(
+                    &key.thumbprint(digest.into())
+                        .into_report(report_style(), "compute thumbprint")?,
+                );
+                output.push('\n');
+            }
+
+            return cli.output.write(output.as_bytes());
+        }
+        #[cfg(feature = "openssl")]
+        KeyType::Verify { key } => {
+            let input = Zeroizing::new(read_input(&key, "PEM")?);
+            if verify_pem(&input).into_report(report_style(), "verify key")? {
+                return cli.output.write(b"PASS\n");
+            }
+
+            cli.output.write(b"FAIL\n")?;
+            process::exit(1);
+        }
+        KeyType::Contains {
+            key,
+            jwks_file,
+            passphrase,
+        } => {
+            let set: JwkSet = serde_json::from_slice(&read_input(&jwks_file, "JWK Set")?)
+                .into_report(report_style(), "parse JWK Set")?;
+            let conversion = Conversion::Auto(passphrase.resolve(is_stdin(&key))?);
+            let (keys, _) = convert_file(&key, &conversion, cli.input_format)?;
+
+            let mut output = String::new();
+            let mut is_missing = false;
+            for key in &keys {
+                match set.find(key).into_report(report_style(), "find key")? {
+                    // A matching key without a `kid` is printed as its thumbprint.
+                    Some(found) => match found.kid() {
+                        Some(kid) => output.push_str(kid),
+                        None => output.push_str(
+                            &found
+                                .thumbprint(ThumbprintHash::Sha256)
+                                .into_report(report_style(), "compute thumbprint")?,
+                        ),
+                    },
+                    None => {
+                        is_missing = true;
+                        continue;
+                    }
+                }
+                output.push('\n');
+            }
+
+            cli.output.write(output.as_bytes())?;
+            if is_missing {
+                eprintln!("A key is not in {}", jwks_file.display());
+                process::exit(1);
+            }
+
+            return Ok(());
+        }
+        #[cfg(feature = "openssl")]
+        KeyType::JwkToPem { jwk } => {
+            let input = read_input(&jwk, "JWK")?;
+            let jwk: Jwk =
+                serde_json::from_slice(&input).into_report(report_style(), "parse JWK")?;
+            let pem = jwk.to_pem().into_report(report_style(), "encode PEM")?;
+
+            return cli.output.write(&pem);
+        }
+        #[cfg(feature = "openssl")]
+        KeyType::JwksToPem {
+            path,
+            out_dir,
+            pem_format,
+        } => {
+            let set: JwkSet = serde_json::from_slice(&read_input(&path, "JWK Set")?)
+                .into_report(report_style(), "parse JWK Set")?;
+            if !cli.output.dry_run {
+                fs::create_dir_all(&out_dir)
+                    .into_report(report_style(), "create output directory")?;
+            }
+            for (index, key) in set.keys.iter().enumerate() {
+                if matches!(key, Jwk::Oct(_)) {
+                    warn(format!(
+                        "skipping key {index}, an oct key has no PEM encoding"
+                    ));
+                    continue;
+                }
+
+                let pem = key
+                    .to_pem_as(pem_format.into())
+                    .into_report(report_style(), "encode PEM")?;
+                cli.output
+                    .write_file(&out_dir.join(split_file_name(key, index, "pem")), &pem)?;
+            }
+
+            return Ok(());
+        }
+        KeyType::Schema { language } => {
+            let output = match language {
+                SchemaLanguage::JsonSchema => serialize(
+                    &schema::json_schema(),
+                    cli.format,
+                    if cli.compact { 0 } else { cli.indent },
+                )?,
+                SchemaLanguage::Typescript => schema::typescript().into_bytes(),
+            };
+
+            return cli.output.write(&output);
+        }
+    };
+
+    if !cli.no_validate {
+        for key in &keys {
+            key.validate().into_report(report_style(), "validate key")?;
+        }
+    }
+    if let Some(min_bits) = cli.min_rsa_bits.or(cli.strict.then_some(2048)) {
+        for key in &keys {
+            key.check_rsa_bits(min_bits)
+                .into_report(report_style(), "check key size")?;
+        }
+    }
+    if cli.strict && cli.alg.is_none() && !cli.no_alg {
+        for key in &keys {
+            key.check_alg_derived()
+                .into_report(report_style(), "derive alg")?;
+        }
+    }
+    if cli.public_only {
+        for key in &mut keys {
+            key.strip_private();
+        }
+    }
+    if cli.no_alg {
+        for key in &mut keys {
+            key.clear_alg();
+        }
+    } else if let Some(alg) = &cli.alg {
+        for key in &mut keys {
+            key.set_alg(alg.clone())
+                .into_report(report_style(), "set alg")?;
+        }
+    }
+    if !cli.key_ops.is_empty() {
+        for key in &mut keys {
+            key.set_key_ops(cli.key_ops.clone());
+        }
+    }
+    if let Some(ext) = cli.ext {
+        for key in &mut keys {
+            key.set_ext(ext);
+        }
+    }
+    if cli.include_point {
+        for key in &mut keys {
+            key.include_point()
+                .into_report(report_style(), "compress point")?;
+        }
+    }
+    if cli.include_bits {
+        for key in &mut keys {
+            key.include_bits()
+                .into_report(report_style(), "compute key size")?;
+        }
+    }
+    #[cfg(feature = "openssl")]
+    if !cli.x5c.is_empty() {
+        let mut x5c = Vec::new();
+        for path in &cli.x5c {
+            x5c.extend(
+                x5c_from_pem(&read_input(path, "certificate")?)
+                    .into_report(report_style(), "build x5c")?,
+            );
+        }
+
+        for key in &mut keys {
+            key.set_x5c(x5c.clone())
+                .into_report(report_style(), "set x5c")?;
+        }
+    }
+    // The `use` is inferred after the `x5c` so the certificate key usage takes precedence.
+    if cli.infer_use {
+        for key in &mut keys {
+            key.infer_use();
+        }
+    }
+    // The `use` is applied after the `x5c` so it overrides the certificate key usage.
+    if let Some(use_) = &cli.use_ {
+        for key in &mut keys {
+            key.set_use(use_.clone());
+        }
+    }
+    if !matches!(cli.kid_hash, Digest::Sha256) {
+        for key in &mut keys {
+            key.set_kid_hash(cli.kid_hash.into())
+                .into_report(report_style(), "set kid")?;
+        }
+    }
+    let kid_format = if cli.kid_uri {
+        pem_params::KidFormat::ThumbprintUri
+    } else {
+        cli.kid_format.into()
+    };
+    for key in &mut keys {
+        key.set_kid_format(kid_format)
+            .into_report(report_style(), "set kid")?;
+    }
+    if let Some(kid) = &cli.kid {
+        for key in &mut keys {
+            key.set_kid(kid.clone());
+        }
+    }
+
+    if cli.summary {
+        let mut output = String::new();
+        for key in &keys {
+            output.push_str(&key.summary().into_report(report_style(), "summarize key")?);
+            output.push('\n');
+        }
+
+        return cli.output.write(output.as_bytes());
+    }
+
+    if cli.coords_hex {
+        let mut output = String::new();
+        for key in &keys {
+            output.push_str(
+                &key.coordinates_hex()
+                    .into_report(report_style(), "print coordinates")?,
+            );
+        }
+
+        return cli.output.write(output.as_bytes());
+    }
+
+    let indent = if cli.compact { 0 } else { cli.indent };
+    if let Some(dir) = split_out {
+        if !cli.output.dry_run {
+            fs::create_dir_all(&dir).into_report(report_style(), "create output directory")?;
+        }
+        let extension = match cli.format {
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+        };
+        for (index, key) in keys.iter().enumerate() {
+            let output = if cli.sort_keys {
+                serialize(&SortedJwk(key), cli.format, indent)
             } else {
-                unimplemented!("The key id {:?} is not implemented", key.id())
-            }
-        }
-        KeyType::Public { key } => {
-            let pem = fs::read(key).into_report(ReportStyle::Coloured, "read PEM file")?;
-            let key =
-                PKey::public_key_from_pem(&pem).into_report(ReportStyle::Coloured, "parse PEM")?;
-
-            if let Ok(ec_key) = key.ec_key() {
-                let output = EcOutput::try_from(ec_key.as_ref())?;
-                let json = serde_json::to_string_pretty(&output)
-                    .into_report(ReportStyle::Coloured, "serialize output")?;
-                println!("{json}");
-            } else if let Ok(rsa_key) = key.rsa() {
-                let output = RsaOutput::try_from(rsa_key.as_ref())?;
-                let json = serde_json::to_string_pretty(&output)
-                    .into_report(ReportStyle::Coloured, "serialize output")?;
-                println!("{json}");
+                serialize(key, cli.format, indent)
+            }?;
+            cli.output
+                .write_file(&dir.join(split_file_name(key, index, extension)), &output)?;
+        }
+
+        return Ok(());
+    }
+
+    if let Some((path, remove_kids)) = merge_into {
+        let set = merge_jwks(&path, &keys, &remove_kids, cli.sort_keys)?;
+        let output = serialize(&set, cli.format, indent)?;
+        return match cli.output.output {
+            Some(_) => cli.output.write(&output),
+            None => cli
+                .output
+                .replace_file(&path, cli.output.trim_newline(&output)),
+        };
+    }
+
+    if cli.ndjson {
+        let mut output = Vec::new();
+        for key in &keys {
+            output.extend(if cli.sort_keys {
+                serialize(&SortedJwk(key), OutputFormat::Json, 0)
             } else {
-                unimplemented!("The key id {:?} is not implemented", key.id())
-            }
+                serialize(key, OutputFormat::Json, 0)
+            }?);
         }
+
+        return cli.output.write(&output);
     }
 
-    Ok(())
+    let set = JwkSet { keys };
+    let output = match (cli.jwks || is_set, cli.sort_keys) {
+        (true, true) => serialize(&SortedJwkSet(&set), cli.format, indent),
+        (true, false) => serialize(&set, cli.format, indent),
+        (false, true) => serialize(&SortedJwk(&set.keys[0]), cli.format, indent),
+        (false, false) => serialize(&set.keys[0], cli.format, indent),
+    }?;
+
+    cli.output.write(&output)
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct EcOutput {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    d: Option<String>,
-    x: String,
-    y: String,
-    kid: String,
-    crv: String,
-    kty: String,
-    alg: String,
-}
-
-impl TryFrom<&EcKeyRef<Public>> for EcOutput {
-    type Error = Report<'static>;
-
-    fn try_from(key: &EcKeyRef<Public>) -> Result<Self, Self::Error> {
-        let mut ctx =
-            BigNumContext::new().into_report(ReportStyle::Coloured, "create big number")?;
-        let mut x = BigNum::new().into_report(ReportStyle::Coloured, "create big number")?;
-        let mut y = BigNum::new().into_report(ReportStyle::Coloured, "create big number")?;
-
-        key.public_key()
-            .affine_coordinates(key.group(), &mut x, &mut y, &mut ctx)
-            .into_report(ReportStyle::Coloured, "extract coordinates")?;
-
-        let base64_x = Base64UrlUnpadded::encode_string(&x.to_vec());
-        let base64_y = Base64UrlUnpadded::encode_string(&y.to_vec());
-
-        let hash = sha256(
-            key.public_key()
-                .to_bytes(key.group(), PointConversionForm::UNCOMPRESSED, &mut ctx)
-                .into_report(ReportStyle::Coloured, "get key bytes")?
-                .as_slice(),
-        );
-        let hash_base64 = Base64UrlUnpadded::encode_string(&hash);
-
-        let crv = match key
-            .group()
-            .curve_name()
-            .into_report(ReportStyle::Coloured, "get curve name")?
+/// Merge the keys into the JWK Set file, removing the keys with the `kid`s first. Keys whose `kid`
+/// is already in the set are skipped, and the other members of the set are left as they are.
+fn merge_jwks(
+    path: &Path,
+    keys: &[Jwk],
+    remove_kids: &[String],
+    sort_keys: bool,
+) -> ReportResult<'static, Value> {
+    let mut set: Value = serde_json::from_slice(&read_input(path, "JWK Set")?)
+        .into_report(report_style(), "parse JWK Set")?;
+    let Some(Value::Array(existing)) = set.get_mut("keys") else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a JWK Set", path.display()),
+        ))
+        .into_report(report_style(), "parse JWK Set");
+    };
+
+    let kid = |key: &Value| key.get("kid").and_then(Value::as_str).map(str::to_owned);
+    existing.retain(|key| kid(key).is_none_or(|kid| !remove_kids.contains(&kid)));
+    for key in keys {
+        if key
+            .kid()
+            .is_some_and(|new| existing.iter().any(|key| kid(key).as_deref() == Some(new)))
         {
-            Nid::X9_62_PRIME256V1 => Some("P-256"), // TODO
-            _ => None,
+            continue;
+        }
+
+        let value = if sort_keys {
+            serde_json::to_value(SortedJwk(key))
+        } else {
+            serde_json::to_value(key)
         };
+        existing.push(value.into_report(report_style(), "serialize key")?);
+    }
 
-        Ok(EcOutput {
-            d: None,
-            x: base64_x,
-            y: base64_y,
-            kty: "EC".to_string(),
-            alg: "ES256".to_string(), // TODO
-            crv: crv.unwrap_or("Unknown").to_string(),
-            kid: hash_base64,
-        })
+    Ok(set)
+}
+
+/// The file name of a key written to an output directory, named by its `kid` when that is a
+/// plain file name, otherwise by its position in the output.
+fn split_file_name(key: &Jwk, index: usize, extension: &str) -> String {
+    match key.kid() {
+        Some(kid)
+            if !kid.is_empty()
+                && kid
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || matches!(char, '-' | '_')) =>
+        {
+            format!("{kid}.{extension}")
+        }
+        _ => format!("{index}.{extension}"),
+    }
+}
+
+/// Convert the keys in the file, returning if the file was a bundle of several PEM blocks.
+fn convert_file(
+    path: &Path,
+    conversion: &Conversion,
+    format: InputFormat,
+) -> ReportResult<'static, (Vec<Jwk>, bool)> {
+    // The input may be a private key, scrub it once converted.
+    let input = Zeroizing::new(match conversion {
+        Conversion::Oct => read_input(path, "secret")?,
+        Conversion::RawOkp(..) => read_input(path, "raw key")?,
+        #[cfg(feature = "openssl")]
+        Conversion::Pkcs12(_) => read_input(path, "PKCS#12")?,
+        Conversion::Ssh => read_input(path, "SSH public key")?,
+        _ => read_input(path, "PEM")?,
+    });
+    let blocks = match (conversion, format) {
+        #[cfg(feature = "openssl")]
+        (Conversion::Pkcs12(_), _) => vec![input.as_slice()],
+        (Conversion::Oct | Conversion::RawOkp(..) | Conversion::Ssh, _) | (_, InputFormat::Der) => {
+            vec![input.as_slice()]
+        }
+        (_, InputFormat::Pem) => {
+            const BEGIN: &[u8] = b"-----BEGIN ";
+
+            let blocks = split_pem(&input);
+            let begins = input
+                .windows(BEGIN.len())
+                .filter(|window| *window == BEGIN)
+                .count();
+            if blocks.is_empty() {
+                let error = if begins == 0 {
+                    PemInputError::Missing(path.to_owned())
+                } else {
+                    PemInputError::Truncated(path.to_owned())
+                };
+                return Err(error).into_report(report_style(), "read PEM");
+            }
+            if begins > blocks.len() {
+                warn(format_args!(
+                    "ignoring the truncated PEM block at the end of {}",
+                    path.display()
+                ));
+            }
+
+            blocks
+        }
+    };
+
+    // A bundle of several PEM blocks becomes a JWK Set, blocks that fail are skipped rather than
+    // failing the whole bundle. Certificates in a bundle of keys are attached to the key they
+    // certify rather than converted.
+    if blocks.len() > 1 {
+        let (certificates, keys): (Vec<_>, Vec<_>) =
+            blocks.iter().enumerate().partition(|(_, block)| {
+                !matches!(conversion, Conversion::Certificate)
+                    && block.starts_with(b"-----BEGIN CERTIFICATE-----")
+            });
+        let key_blocks = keys.len();
+
+        let mut keys: Vec<_> = keys
+            .into_iter()
+            .filter_map(|(index, block)| match conversion.convert(format, block) {
+                Ok(jwk) => Some(jwk),
+                Err(report) => {
+                    warn(format_args!("skipping PEM block {}: {report}", index + 1));
+                    None
+                }
+            })
+            .collect();
+
+        attach_bundled_certificates(&mut keys, &certificates)?;
+
+        // A key bundled with its certificates is still a single key.
+        let is_set = key_blocks > 1 || keys.is_empty();
+        Ok((keys, is_set))
+    } else {
+        Ok((vec![conversion.convert(format, &input)?], false))
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct RsaOutput {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    d: Option<String>,
-    n: String,
-    e: String,
-    kid: String,
-    kty: String,
-    alg: String,
+/// Attach the certificate blocks of a bundle, numbered by their position in the bundle, to the keys
+/// they certify.
+#[cfg(feature = "openssl")]
+fn attach_bundled_certificates(
+    keys: &mut [Jwk],
+    certificates: &[(usize, &&[u8])],
+) -> ReportResult<'static, ()> {
+    let blocks: Vec<&[u8]> = certificates.iter().map(|(_, block)| **block).collect();
+    let unmatched =
+        attach_certificates(keys, &blocks).into_report(report_style(), "attach certificates")?;
+    for (index, error) in unmatched {
+        let block = certificates[index].0 + 1;
+        match error {
+            Some(error) => warn(format_args!(
+                "ignoring PEM block {block}, the certificate is invalid: {error}"
+            )),
+            None => warn(format_args!(
+                "ignoring PEM block {block}, the certificate matches no key"
+            )),
+        }
+    }
+
+    Ok(())
 }
 
-impl TryFrom<&RsaRef<Public>> for RsaOutput {
-    type Error = Report<'static>;
+/// Attach the certificate blocks of a bundle to the keys they certify, which needs OpenSSL to
+/// parse the certificates.
+#[cfg(not(feature = "openssl"))]
+fn attach_bundled_certificates(
+    _keys: &mut [Jwk],
+    certificates: &[(usize, &&[u8])],
+) -> ReportResult<'static, ()> {
+    for (index, _) in certificates {
+        warn(format_args!(
+            "ignoring PEM block {}, certificates need the `openssl` feature",
+            index + 1
+        ));
+    }
 
-    fn try_from(key: &RsaRef<Public>) -> Result<Self, Self::Error> {
-        let base64_n = Base64UrlUnpadded::encode_string(&key.n().to_vec());
-        let base64_e = Base64UrlUnpadded::encode_string(&key.e().to_vec());
+    Ok(())
+}
 
-        let mut bytes = vec![];
-        bytes.extend_from_slice(&key.n().to_vec());
-        bytes.extend_from_slice(&key.e().to_vec());
-        let hash = sha256(&bytes);
-        let hash_base64 = Base64UrlUnpadded::encode_string(&hash);
+/// Convert every key file in the directory in parallel, skipping files that fail and keys that
+/// share a `kid` with an earlier key.
+fn convert_directory(
+    dir: &Path,
+    recursive: bool,
+    format: InputFormat,
+) -> ReportResult<'static, Vec<Jwk>> {
+    let mut paths = Vec::new();
+    collect_key_files(dir, recursive, &mut HashSet::new(), &mut paths)?;
+    paths.sort();
 
-        Ok(RsaOutput {
-            d: None,
-            n: base64_n,
-            e: base64_e,
-            kty: "RSA".to_string(),
-            alg: "RS256".to_string(),
-            kid: hash_base64,
+    // Reports are not `Send`, so they are rendered on the worker threads. The results keep the
+    // order of the sorted paths, so the output and warnings do not depend on scheduling.
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            let conversion = match path.extension().and_then(OsStr::to_str) {
+                Some("crt") => Conversion::Certificate,
+                _ => Conversion::Auto(None),
+            };
+            convert_file(path, &conversion, format).map_err(|report| report.to_string())
         })
+        .collect();
+
+    let mut kids = HashSet::new();
+    let mut keys = Vec::new();
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok((file_keys, _)) => keys.extend(
+                file_keys
+                    .into_iter()
+                    .filter(|key| key.kid().is_none_or(|kid| kids.insert(kid.to_owned()))),
+            ),
+            Err(report) => warn(format_args!("skipping {}: {report}", path.display())),
+        }
     }
+
+    Ok(keys)
 }
-impl TryFrom<&RsaRef<Private>> for RsaOutput {
-    type Error = Report<'static>;
-
-    fn try_from(key: &RsaRef<Private>) -> Result<Self, Self::Error> {
-        let base64_n = Base64UrlUnpadded::encode_string(&key.n().to_vec());
-        let base64_e = Base64UrlUnpadded::encode_string(&key.e().to_vec());
-        let base64_d = Base64UrlUnpadded::encode_string(&key.d().to_vec());
-
-        let mut bytes = vec![];
-        bytes.extend_from_slice(&key.n().to_vec());
-        bytes.extend_from_slice(&key.e().to_vec());
-        bytes.extend_from_slice(&key.d().to_vec());
-        let hash = sha256(&bytes);
-        let hash_base64 = Base64UrlUnpadded::encode_string(&hash);
-
-        Ok(RsaOutput {
-            d: Some(base64_d),
-            n: base64_n,
-            e: base64_e,
-            kty: "RSA".to_string(),
-            alg: "RS256".to_string(),
-            kid: hash_base64,
-        })
+
+/// Collect the files in the directory, when recursive only the `.pem`, `.key`, and `.crt` files of
+/// the directory and its subdirectories are collected.
+fn collect_key_files(
+    dir: &Path,
+    recursive: bool,
+    visited: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> ReportResult<'static, ()> {
+    // Symlinks may lead back to a directory that was already walked, walk each directory once.
+    let canonical = fs::canonicalize(dir).into_report(report_style(), "read key directory")?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir).into_report(report_style(), "read key directory")? {
+        let path = entry
+            .into_report(report_style(), "read key directory")?
+            .path();
+        if path.is_file() {
+            let is_key_file = matches!(
+                path.extension().and_then(OsStr::to_str),
+                Some("pem" | "key" | "crt")
+            );
+            if !recursive || is_key_file {
+                paths.push(path);
+            }
+        } else if recursive && path.is_dir() {
+            collect_key_files(&path, recursive, visited, paths)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// How the keys in the input are converted.
+enum Conversion {
+    Private(Option<String>),
+    Public,
+    Auto(Option<String>),
+    Certificate,
+    #[cfg(feature = "openssl")]
+    Pkcs12(String),
+    Ssh,
+    Oct,
+    RawOkp(String, bool),
+}
+
+impl Conversion {
+    /// Convert a single key from the input.
+    fn convert(&self, format: InputFormat, input: &[u8]) -> ReportResult<'static, Jwk> {
+        let result = match (self, format) {
+            #[cfg(feature = "openssl")]
+            (Self::Private(Some(passphrase)) | Self::Auto(Some(passphrase)), InputFormat::Pem) => {
+                encrypted_pem_to_jwk(input, passphrase.as_bytes())
+            }
+            #[cfg(not(feature = "openssl"))]
+            (Self::Private(Some(_)) | Self::Auto(Some(_)), InputFormat::Pem) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "decrypting a key needs the `openssl` feature",
+                ))
+                .into_report(report_style(), "convert key");
+            }
+            (Self::Private(_), InputFormat::Pem) => pem_to_jwk(input, true),
+            (Self::Private(_), InputFormat::Der) => der_to_jwk(input, true),
+            (Self::Public, InputFormat::Pem) => pem_to_jwk(input, false),
+            (Self::Public, InputFormat::Der) => der_to_jwk(input, false),
+            (Self::Auto(_), InputFormat::Pem) => detect_pem_to_jwk(input),
+            (Self::Auto(_), InputFormat::Der) => detect_der_to_jwk(input),
+            #[cfg(feature = "openssl")]
+            (Self::Certificate, InputFormat::Pem) => certificate_pem_to_jwk(input),
+            #[cfg(feature = "openssl")]
+            (Self::Certificate, InputFormat::Der) => certificate_der_to_jwk(input),
+            #[cfg(not(feature = "openssl"))]
+            (Self::Certificate, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "converting a certificate needs the `openssl` feature",
+                ))
+                .into_report(report_style(), "convert key");
+            }
+            #[cfg(feature = "openssl")]
+            (Self::Pkcs12(passphrase), _) => pkcs12_to_jwk(input, passphrase),
+            (Self::Ssh, _) => ssh_to_jwk(input),
+            (Self::Oct, _) => secret_to_jwk(input),
+            (Self::RawOkp(crv, is_private), _) => raw_okp_to_jwk(input, crv, *is_private),
+        };
+
+        // Without a passphrase an encrypted key can only be converted by prompting for one.
+        match result {
+            #[cfg(feature = "openssl")]
+            Err(Error::EncryptedKey) if io::stdin().is_terminal() => {
+                // Batch conversions run in parallel, prompt for one key at a time.
+                let _prompt = PROMPT.lock().unwrap_or_else(PoisonError::into_inner);
+                let passphrase = Zeroizing::new(
+                    rpassword::prompt_password("Passphrase: ")
+                        .into_report(report_style(), "read passphrase")?,
+                );
+                encrypted_pem_to_jwk(input, passphrase.as_bytes())
+                    .into_report(report_style(), "convert key")
+            }
+            // A key of the other kind is converted as that kind when lenient, otherwise point at
+            // the subcommand for it.
+            Err(Error::UnexpectedKeyKind { found, .. }) if LENIENT.load(Ordering::Relaxed) => {
+                warn(format_args!(
+                    "the key is a {found} key, converting it as one"
+                ));
+                let conversion = match self {
+                    Self::Public => Self::Private(None),
+                    _ => Self::Public,
+                };
+                conversion.convert(format, input)
+            }
+            Err(error @ Error::UnexpectedKeyKind { found, .. }) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{error}, use the `{found}` subcommand or --lenient"),
+            ))
+            .into_report(report_style(), "convert key"),
+            result => result.into_report(report_style(), "convert key"),
+        }
+        .inspect(|jwk| debug!(kid = jwk.kid(), "converted key"))
+    }
+}
+
+/// Serialize the value in the output format, JSON is pretty printed with this many spaces of
+/// indentation or on a single line if it is zero.
+fn serialize<T: Serialize>(
+    value: &T,
+    format: OutputFormat,
+    indent: usize,
+) -> ReportResult<'static, Vec<u8>> {
+    let mut output = match format {
+        OutputFormat::Json if indent == 0 => {
+            serde_json::to_vec(value).into_report(report_style(), "serialize output")?
+        }
+        OutputFormat::Json => {
+            let indent = vec![b' '; indent];
+            let mut json = Vec::new();
+            let mut serializer =
+                Serializer::with_formatter(&mut json, PrettyFormatter::with_indent(&indent));
+            value
+                .serialize(&mut serializer)
+                .into_report(report_style(), "serialize output")?;
+            json
+        }
+        OutputFormat::Yaml => {
+            return serde_yaml::to_string(value)
+                .map(String::into_bytes)
+                .into_report(report_style(), "serialize output");
+        }
+    };
+
+    output.push(b'\n');
+    Ok(output)
+}
+
+/// If the path is `-`, which reads from stdin.
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Read the input from the path, or from stdin if the path is `-`.
+fn read_input(path: &Path, kind: &str) -> ReportResult<'static, Vec<u8>> {
+    debug!(path = %path.display(), "reading {kind}");
+
+    if is_stdin(path) {
+        let mut input = Vec::new();
+        io::stdin()
+            .read_to_end(&mut input)
+            .into_report(report_style(), format!("read {kind} from stdin"))?;
+        Ok(input)
+    } else {
+        fs::read(path).into_report(report_style(), format!("read {kind} file"))
     }
 }
@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, thumbprint};
+
+/// A symmetric JWK.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OctOutput {
+    pub k: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alg: String,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<bool>,
+}
+
+impl TryFrom<&[u8]> for OctOutput {
+    type Error = Error;
+
+    fn try_from(secret: &[u8]) -> Result<Self, Self::Error> {
+        if secret.is_empty() {
+            return Err(Error::EmptySecret);
+        }
+
+        let base64_k = Base64UrlUnpadded::encode_string(secret);
+
+        let kid = thumbprint(BTreeMap::from([("k", base64_k.as_str()), ("kty", "oct")]))?;
+
+        Ok(OctOutput {
+            k: base64_k,
+            kty: "oct".to_string(),
+            alg: "HS256".to_string(),
+            kid: Some(kid),
+            use_: None,
+            key_ops: None,
+            ext: None,
+        })
+    }
+}
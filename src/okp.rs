@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+#[cfg(feature = "openssl")]
+use openssl::{
+    nid::Nid,
+    pkey::{Id, PKey, PKeyRef, Private, Public},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openssl")]
+use zeroize::Zeroizing;
+
+use crate::{alg::default_okp_alg, error::Error, thumbprint};
+#[cfg(feature = "openssl")]
+use crate::{decode_member, error::ResultExt};
+
+/// An octet key pair JWK, the `alg` defaults from the curve:
+/// - `Ed25519` and `Ed448` are signing curves, `alg: "EdDSA"`.
+/// - `X25519` and `X448` are key agreement curves, `alg: "ECDH-ES"`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OkpOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    pub x: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    pub crv: String,
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alg: String,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+}
+
+impl OkpOutput {
+    /// Create the public JWK from the raw public key bytes on the curve.
+    pub(crate) fn from_public_bytes(crv: &str, x: &[u8]) -> Result<Self, Error> {
+        let base64_x = Base64UrlUnpadded::encode_string(x);
+
+        let alg = default_okp_alg(crv).ok_or_else(|| Error::UnsupportedCurve(crv.to_string()))?;
+
+        let kid = thumbprint(BTreeMap::from([
+            ("crv", crv),
+            ("kty", "OKP"),
+            ("x", &base64_x),
+        ]))?;
+
+        Ok(OkpOutput {
+            d: None,
+            x: base64_x,
+            kty: "OKP".to_string(),
+            alg: alg.to_string(),
+            crv: crv.to_string(),
+            kid: Some(kid),
+            use_: None,
+            key_ops: None,
+            ext: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+        })
+    }
+
+    /// Reconstruct the key as a PEM, a PKCS#8 private key if `d` is present.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem(&self) -> Result<Vec<u8>, Error> {
+        let id = okp_id(&self.crv)?;
+
+        match &self.d {
+            Some(d) => {
+                PKey::private_key_from_raw_bytes(&Zeroizing::new(decode_member(d, "d")?), id)
+                    .backend_err("reconstruct private key")?
+                    .private_key_to_pem_pkcs8()
+                    .backend_err("encode private key PEM")
+            }
+            None => PKey::public_key_from_raw_bytes(&decode_member(&self.x, "x")?, id)
+                .backend_err("reconstruct public key")?
+                .public_key_to_pem()
+                .backend_err("encode public key PEM"),
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&PKeyRef<Public>> for OkpOutput {
+    type Error = Error;
+
+    fn try_from(key: &PKeyRef<Public>) -> Result<Self, Self::Error> {
+        let x = key.raw_public_key().backend_err("get raw public key")?;
+        let crv = match key.id() {
+            Id::ED25519 => "Ed25519",
+            Id::ED448 => "Ed448",
+            Id::X25519 => "X25519",
+            Id::X448 => "X448",
+            id => {
+                let nid = Nid::from_raw(id.as_raw());
+                return Err(Error::UnsupportedCurve(
+                    nid.short_name().unwrap_or("unknown").to_string(),
+                ));
+            }
+        };
+        Self::from_public_bytes(crv, &x)
+    }
+}
+#[cfg(feature = "openssl")]
+impl TryFrom<&PKeyRef<Private>> for OkpOutput {
+    type Error = Error;
+
+    fn try_from(key: &PKeyRef<Private>) -> Result<Self, Self::Error> {
+        let x = key.raw_public_key().backend_err("get raw public key")?;
+        let public_key =
+            PKey::public_key_from_raw_bytes(&x, key.id()).backend_err("extract public key")?;
+        let mut output = Self::try_from(public_key.as_ref())?;
+
+        let d = Zeroizing::new(key.raw_private_key().backend_err("get raw private key")?);
+        let base64_d = Base64UrlUnpadded::encode_string(&d);
+        output.d = Some(base64_d);
+
+        Ok(output)
+    }
+}
+
+/// The length in bytes of a raw public or private key on the OKP `crv`.
+pub(crate) fn key_length(crv: &str) -> Result<usize, Error> {
+    match crv {
+        "Ed25519" | "X25519" => Ok(32),
+        "Ed448" => Ok(57),
+        "X448" => Ok(56),
+        crv => Err(Error::UnsupportedCurve(crv.to_string())),
+    }
+}
+
+/// The OpenSSL key id for the OKP `crv`.
+#[cfg(feature = "openssl")]
+pub(crate) fn okp_id(crv: &str) -> Result<Id, Error> {
+    match crv {
+        "Ed25519" => Ok(Id::ED25519),
+        "Ed448" => Ok(Id::ED448),
+        "X25519" => Ok(Id::X25519),
+        "X448" => Ok(Id::X448),
+        crv => Err(Error::UnsupportedCurve(crv.to_string())),
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+#[cfg(feature = "openssl")]
+use openssl::{
+    bn::{BigNum, BigNumContext, BigNumRef},
+    pkey::{HasPublic, Id, PKey, PKeyRef, Private, Public},
+    rsa::{Rsa, RsaRef},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openssl")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "openssl")]
+use crate::{PemFormat, decode_big_num, error::ResultExt};
+use crate::{error::Error, thumbprint};
+
+/// An RSA JWK.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RsaOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dq: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qi: Option<String>,
+    pub n: String,
+    pub e: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alg: String,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    #[serde(rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub x5t_s256: Option<String>,
+    /// The size of the modulus in bits, this is not a JWK member.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bits: Option<usize>,
+    /// If the key is restricted to RSASSA-PSS by its AlgorithmIdentifier, so only the `PS*`
+    /// algorithms may be used with it.
+    #[serde(skip)]
+    pub(crate) is_pss: bool,
+}
+
+impl RsaOutput {
+    /// Create the public JWK from the big-endian modulus and exponent of the key.
+    pub(crate) fn from_components(n: &[u8], e: &[u8]) -> Result<Self, Error> {
+        let base64_n = Base64UrlUnpadded::encode_string(unsigned(n));
+        let base64_e = Base64UrlUnpadded::encode_string(unsigned(e));
+
+        let kid = thumbprint(BTreeMap::from([
+            ("e", base64_e.as_str()),
+            ("kty", "RSA"),
+            ("n", &base64_n),
+        ]))?;
+
+        Ok(RsaOutput {
+            d: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            n: base64_n,
+            e: base64_e,
+            kty: "RSA".to_string(),
+            alg: "RS256".to_string(),
+            kid: Some(kid),
+            use_: None,
+            key_ops: None,
+            ext: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            bits: None,
+            is_pss: false,
+        })
+    }
+
+    /// Restrict the key to RSASSA-PSS with the `alg` for the hash of its parameters.
+    pub(crate) fn restrict_to_pss(&mut self, alg: &str) {
+        self.alg = alg.to_owned();
+        self.is_pss = true;
+    }
+
+    /// Check that `n` is `p * q`, that `d` is the private exponent for `e`, and that the CRT
+    /// members are derived from them. A public key, or a private key without its primes, has
+    /// nothing to check.
+    #[cfg(feature = "openssl")]
+    pub fn validate(&self) -> Result<(), Error> {
+        let (Some(d), Some(p), Some(q)) = (&self.d, &self.p, &self.q) else {
+            return Ok(());
+        };
+        let n = decode_big_num(&self.n, "n")?;
+        let e = decode_big_num(&self.e, "e")?;
+        let d = decode_big_num(d, "d")?;
+        let p = decode_big_num(p, "p")?;
+        let q = decode_big_num(q, "q")?;
+
+        let mut ctx = BigNumContext::new().backend_err("create big number")?;
+        let one = BigNum::from_u32(1).backend_err("create big number")?;
+        let new = || BigNum::new().backend_err("create big number");
+
+        let mut product = new()?;
+        product
+            .checked_mul(&p, &q, &mut ctx)
+            .backend_err("multiply primes")?;
+        if product != n {
+            return Err(Error::InconsistentRsaKey("`n` is not `p * q`"));
+        }
+
+        // `d` may be reduced modulo either Euler's or Carmichael's totient, the inverse of `e`
+        // modulo lcm(p - 1, q - 1) accepts both.
+        let (mut p1, mut q1) = (new()?, new()?);
+        p1.checked_sub(&p, &one).backend_err("subtract one")?;
+        q1.checked_sub(&q, &one).backend_err("subtract one")?;
+        let (mut gcd, mut totient, mut lambda) = (new()?, new()?, new()?);
+        gcd.gcd(&p1, &q1, &mut ctx).backend_err("compute gcd")?;
+        totient
+            .checked_mul(&p1, &q1, &mut ctx)
+            .backend_err("compute totient")?;
+        lambda
+            .checked_div(&totient, &gcd, &mut ctx)
+            .backend_err("compute totient")?;
+
+        let mut de = new()?;
+        de.mod_mul(&d, &e, &lambda, &mut ctx)
+            .backend_err("multiply exponents")?;
+        if de != one {
+            return Err(Error::InconsistentRsaKey(
+                "`d` is not the private exponent for `e`",
+            ));
+        }
+
+        let mut expected = new()?;
+        if let Some(dp) = &self.dp {
+            expected
+                .nnmod(&d, &p1, &mut ctx)
+                .backend_err("reduce exponent")?;
+            if decode_big_num(dp, "dp")? != expected {
+                return Err(Error::InconsistentRsaKey("`dp` is not `d mod (p - 1)`"));
+            }
+        }
+        if let Some(dq) = &self.dq {
+            expected
+                .nnmod(&d, &q1, &mut ctx)
+                .backend_err("reduce exponent")?;
+            if decode_big_num(dq, "dq")? != expected {
+                return Err(Error::InconsistentRsaKey("`dq` is not `d mod (q - 1)`"));
+            }
+        }
+        if let Some(qi) = &self.qi {
+            let qi = decode_big_num(qi, "qi")?;
+            expected
+                .mod_mul(&qi, &q, &p, &mut ctx)
+                .backend_err("multiply coefficient")?;
+            if expected != one {
+                return Err(Error::InconsistentRsaKey(
+                    "`qi` is not the inverse of `q` mod `p`",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that `n` is `p * q`, that `d` is the private exponent for `e`, and that the CRT
+    /// members are derived from them. A public key, or a private key without its primes, has
+    /// nothing to check.
+    #[cfg(not(feature = "openssl"))]
+    pub fn validate(&self) -> Result<(), Error> {
+        crate::rustcrypto::validate_rsa(self)
+    }
+
+    /// Reconstruct the key as a PEM, a PKCS#8 private key if `d` is present.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem(&self) -> Result<Vec<u8>, Error> {
+        self.to_pem_as(PemFormat::Pkcs8)
+    }
+
+    /// Reconstruct the key as a PEM, the traditional format is PKCS#1 for both private and public
+    /// keys.
+    #[cfg(feature = "openssl")]
+    pub fn to_pem_as(&self, format: PemFormat) -> Result<Vec<u8>, Error> {
+        let n = decode_big_num(&self.n, "n")?;
+        let e = decode_big_num(&self.e, "e")?;
+
+        match &self.d {
+            Some(d) => {
+                let required =
+                    |value: &Option<String>, member: &'static str| -> Result<BigNum, Error> {
+                        let value = value.as_deref().ok_or(Error::MissingMember(member))?;
+                        decode_big_num(value, member)
+                    };
+
+                let private_key = Rsa::from_private_components(
+                    n,
+                    e,
+                    decode_big_num(d, "d")?,
+                    required(&self.p, "p")?,
+                    required(&self.q, "q")?,
+                    required(&self.dp, "dp")?,
+                    required(&self.dq, "dq")?,
+                    required(&self.qi, "qi")?,
+                )
+                .backend_err("reconstruct private key")?;
+                match format {
+                    PemFormat::Pkcs8 => PKey::from_rsa(private_key)
+                        .backend_err("wrap private key")?
+                        .private_key_to_pem_pkcs8(),
+                    PemFormat::Traditional => private_key.private_key_to_pem(),
+                }
+                .backend_err("encode private key PEM")
+            }
+            None => {
+                let public_key =
+                    Rsa::from_public_components(n, e).backend_err("reconstruct public key")?;
+                match format {
+                    PemFormat::Pkcs8 => PKey::from_rsa(public_key)
+                        .backend_err("wrap public key")?
+                        .public_key_to_pem(),
+                    PemFormat::Traditional => public_key.public_key_to_pem_pkcs1(),
+                }
+                .backend_err("encode public key PEM")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&RsaRef<Public>> for RsaOutput {
+    type Error = Error;
+
+    fn try_from(key: &RsaRef<Public>) -> Result<Self, Self::Error> {
+        Self::from_components(&key.n().to_vec(), &key.e().to_vec())
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl TryFrom<&RsaRef<Private>> for RsaOutput {
+    type Error = Error;
+
+    fn try_from(key: &RsaRef<Private>) -> Result<Self, Self::Error> {
+        let n = key.n().to_owned().backend_err("copy modulus")?;
+        let e = key.e().to_owned().backend_err("copy exponent")?;
+        let public_key = Rsa::from_public_components(n, e).backend_err("extract public key")?;
+        let mut output = Self::try_from(public_key.as_ref())?;
+
+        let base64_d = Base64UrlUnpadded::encode_string(&Zeroizing::new(key.d().to_vec()));
+        output.d = Some(base64_d);
+
+        let encode = |value: Option<&BigNumRef>| {
+            value.map(|value| Base64UrlUnpadded::encode_string(&Zeroizing::new(value.to_vec())))
+        };
+        output.p = encode(key.p());
+        output.q = encode(key.q());
+        output.dp = encode(key.dmp1());
+        output.dq = encode(key.dmq1());
+        output.qi = encode(key.iqmp());
+
+        Ok(output)
+    }
+}
+
+/// The minimal big-endian unsigned encoding of the integer, without any leading zero sign byte.
+fn unsigned(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len().saturating_sub(1));
+    &bytes[start..]
+}
+
+/// DER encoding of the SHA-384 object identifier.
+const SHA384_OID: &[u8] = &[
+    0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02,
+];
+/// DER encoding of the SHA-512 object identifier.
+const SHA512_OID: &[u8] = &[
+    0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03,
+];
+
+/// The `alg` for an RSASSA-PSS key, derived from the hash its parameters restrict it to, or `None`
+/// if the key is not an RSASSA-PSS key.
+#[cfg(feature = "openssl")]
+pub(crate) fn pss_alg<T: HasPublic>(key: &PKeyRef<T>) -> Option<&'static str> {
+    if key.id() != Id::RSA_PSS {
+        return None;
+    }
+
+    // The hash restriction lives in the parameters of the SubjectPublicKeyInfo's
+    // AlgorithmIdentifier, an unrestricted key has no parameters.
+    let spki = key.public_key_to_der().ok();
+    let algorithm = spki
+        .as_deref()
+        .and_then(algorithm_identifier)
+        .unwrap_or_default();
+    Some(pss_hash_alg(algorithm))
+}
+
+/// The `alg` for an RSASSA-PSS key from its DER encoded AlgorithmIdentifier.
+pub(crate) fn pss_hash_alg(algorithm: &[u8]) -> &'static str {
+    let contains = |oid: &[u8]| algorithm.windows(oid.len()).any(|window| window == oid);
+
+    if contains(SHA512_OID) {
+        "PS512"
+    } else if contains(SHA384_OID) {
+        "PS384"
+    } else {
+        "PS256"
+    }
+}
+
+/// The AlgorithmIdentifier of a DER encoded SubjectPublicKeyInfo.
+#[cfg(feature = "openssl")]
+fn algorithm_identifier(spki: &[u8]) -> Option<&[u8]> {
+    let (header, _) = der_header(spki)?;
+    let algorithm = spki.get(header..)?;
+    let (header, length) = der_header(algorithm)?;
+    algorithm.get(..header + length)
+}
+
+/// The header and content length of a DER element.
+#[cfg(feature = "openssl")]
+pub(crate) fn der_header(der: &[u8]) -> Option<(usize, usize)> {
+    let length = *der.get(1)?;
+    if length < 0x80 {
+        return Some((2, usize::from(length)));
+    }
+
+    let count = usize::from(length & 0x7f);
+    let length = der
+        .get(2..2 + count)?
+        .iter()
+        .fold(0, |length, byte| (length << 8) | usize::from(*byte));
+    Some((2 + count, length))
+}
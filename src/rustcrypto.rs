@@ -0,0 +1,400 @@
+//! PEM and DER key parsing with the RustCrypto crates instead of OpenSSL, enabled by the
+//! `rustcrypto` feature. Without the `openssl` feature the keys are also validated and hashed
+//! here.
+//!
+//! Private Ed448 and X448 keys and the Brainpool curves are not supported by this backend, there
+//! are no RustCrypto implementations of them built on the same `elliptic-curve` release as the other
+//! curves.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use elliptic_curve::{
+    AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey, SecretKey,
+    sec1::{Coordinates, FromEncodedPoint, ModulusSize, ToEncodedPoint},
+};
+use pkcs1::{RsaPrivateKey, RsaPublicKey};
+use pkcs8::{
+    ObjectIdentifier, PrivateKeyInfo,
+    der::{Decode, Encode, asn1::OctetStringRef, pem},
+    spki::SubjectPublicKeyInfoRef,
+};
+#[cfg(not(feature = "openssl"))]
+use rsa::BigUint;
+use sec1::EcPrivateKey;
+#[cfg(not(feature = "openssl"))]
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use zeroize::Zeroizing;
+
+use crate::{
+    Curve, EcOutput, Jwk, OkpOutput, RsaOutput,
+    error::{Error, ResultExt},
+    rsa::pss_hash_alg,
+    split_pem,
+};
+#[cfg(not(feature = "openssl"))]
+use crate::{ThumbprintHash, decode_member};
+
+const EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const RSASSA_PSS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
+
+const X25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.110");
+const X448: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.111");
+const ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+const ED448: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.113");
+
+const PRIME256V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const SECP384R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+const SECP521R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.35");
+const SECP256K1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.10");
+
+/// Parse the first key in the PEM, skipping any other blocks like OpenSSL does.
+pub(crate) fn parse_pem(pem: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    let labels = labels(is_private);
+
+    let (label, der) = split_pem(pem)
+        .into_iter()
+        .filter_map(|block| pem::decode_vec(block).ok())
+        .map(|(label, der)| (label, Zeroizing::new(der)))
+        .find(|(label, _)| labels.contains(label))
+        .ok_or(Error::MissingPemKey)?;
+
+    decode(label, &der, "PEM")
+}
+
+/// Parse a DER key. DER has no label to select the encoding, so each encoding of the kind is tried
+/// in turn like OpenSSL does.
+pub(crate) fn parse_der(der: &[u8], is_private: bool) -> Result<Jwk, Error> {
+    let mut first_error = None;
+    for label in labels(is_private) {
+        match decode(label, der, "DER") {
+            Err(error @ Error::Parse { .. }) => {
+                first_error.get_or_insert(error);
+            }
+            result => return result,
+        }
+    }
+
+    Err(first_error.unwrap_or(Error::MissingPemKey))
+}
+
+/// The PEM labels of the encodings of a private or public key.
+fn labels(is_private: bool) -> &'static [&'static str] {
+    if is_private {
+        &["PRIVATE KEY", "EC PRIVATE KEY", "RSA PRIVATE KEY"]
+    } else {
+        &["PUBLIC KEY", "RSA PUBLIC KEY"]
+    }
+}
+
+/// Convert the DER key in the encoding of the PEM label.
+fn decode(label: &str, der: &[u8], input: &'static str) -> Result<Jwk, Error> {
+    match label {
+        "PRIVATE KEY" => private_key_info(der, input),
+        "EC PRIVATE KEY" => {
+            let key = EcPrivateKey::from_der(der).parse_err(input)?;
+            let curve = curve(
+                key.parameters
+                    .and_then(|parameters| parameters.named_curve()),
+            )?;
+            Ok(Jwk::Ec(ec_private(curve, key.private_key)?))
+        }
+        "RSA PRIVATE KEY" => {
+            let key = RsaPrivateKey::from_der(der).parse_err(input)?;
+            Ok(Jwk::Rsa(rsa_private(&key)?))
+        }
+        "RSA PUBLIC KEY" => {
+            let key = RsaPublicKey::from_der(der).parse_err(input)?;
+            Ok(Jwk::Rsa(RsaOutput::from_components(
+                key.modulus.as_bytes(),
+                key.public_exponent.as_bytes(),
+            )?))
+        }
+        _ => public_key_info(der, input),
+    }
+}
+
+/// Convert a PKCS#8 private key.
+fn private_key_info(der: &[u8], input: &'static str) -> Result<Jwk, Error> {
+    let info = PrivateKeyInfo::from_der(der).parse_err(input)?;
+
+    match info.algorithm.oid {
+        EC_PUBLIC_KEY => {
+            let key = EcPrivateKey::from_der(info.private_key).parse_err("EC private key")?;
+            let curve = curve(info.algorithm.parameters_oid().ok())?;
+            Ok(Jwk::Ec(ec_private(curve, key.private_key)?))
+        }
+        RSA_ENCRYPTION | RSASSA_PSS => {
+            let key = RsaPrivateKey::from_der(info.private_key).parse_err("RSA private key")?;
+            let mut output = rsa_private(&key)?;
+            if info.algorithm.oid == RSASSA_PSS {
+                let algorithm = info
+                    .algorithm
+                    .to_der()
+                    .backend_err("encode algorithm identifier")?;
+                output.restrict_to_pss(pss_hash_alg(&algorithm));
+            }
+            Ok(Jwk::Rsa(output))
+        }
+        oid @ (ED25519 | ED448 | X25519 | X448) => {
+            // The private key is the raw key wrapped in an OCTET STRING.
+            let d = OctetStringRef::from_der(info.private_key).parse_err("OKP private key")?;
+            okp_private(okp_crv(oid), d.as_bytes()).map(Jwk::Okp)
+        }
+        oid => Err(Error::UnsupportedKeyType(oid.to_string())),
+    }
+}
+
+/// Convert a SubjectPublicKeyInfo public key.
+fn public_key_info(der: &[u8], input: &'static str) -> Result<Jwk, Error> {
+    let info = SubjectPublicKeyInfoRef::from_der(der).parse_err(input)?;
+    let public_key = info.subject_public_key.raw_bytes();
+
+    match info.algorithm.oid {
+        EC_PUBLIC_KEY => {
+            let curve = curve(info.algorithm.parameters_oid().ok())?;
+            Ok(Jwk::Ec(ec_public(curve, public_key)?))
+        }
+        RSA_ENCRYPTION | RSASSA_PSS => {
+            let key = RsaPublicKey::from_der(public_key).parse_err("RSA public key")?;
+            let mut output =
+                RsaOutput::from_components(key.modulus.as_bytes(), key.public_exponent.as_bytes())?;
+            if info.algorithm.oid == RSASSA_PSS {
+                let algorithm = info
+                    .algorithm
+                    .to_der()
+                    .backend_err("encode algorithm identifier")?;
+                output.restrict_to_pss(pss_hash_alg(&algorithm));
+            }
+            Ok(Jwk::Rsa(output))
+        }
+        oid @ (ED25519 | ED448 | X25519 | X448) => {
+            OkpOutput::from_public_bytes(okp_crv(oid), public_key).map(Jwk::Okp)
+        }
+        oid => Err(Error::UnsupportedKeyType(oid.to_string())),
+    }
+}
+
+/// The OKP `crv` for the key algorithm.
+fn okp_crv(oid: ObjectIdentifier) -> &'static str {
+    match oid {
+        ED25519 => "Ed25519",
+        ED448 => "Ed448",
+        X25519 => "X25519",
+        _ => "X448",
+    }
+}
+
+/// Convert a raw OKP private key, deriving its public key.
+pub(crate) fn okp_private(crv: &str, d: &[u8]) -> Result<OkpOutput, Error> {
+    let x = match crv {
+        "Ed25519" => {
+            let seed = Zeroizing::new(<[u8; 32]>::try_from(d).parse_err("Ed25519 private key")?);
+            ed25519_dalek::SigningKey::from_bytes(&seed)
+                .verifying_key()
+                .to_bytes()
+        }
+        "X25519" => {
+            let secret = x25519_dalek::StaticSecret::from(
+                <[u8; 32]>::try_from(d).parse_err("X25519 private key")?,
+            );
+            x25519_dalek::PublicKey::from(&secret).to_bytes()
+        }
+        crv => return Err(Error::UnsupportedCurve(crv.to_string())),
+    };
+
+    let mut output = OkpOutput::from_public_bytes(crv, &x)?;
+    output.d = Some(Base64UrlUnpadded::encode_string(d));
+
+    Ok(output)
+}
+
+/// Parse the raw OKP key.
+#[cfg(not(feature = "openssl"))]
+pub(crate) fn parse_raw_okp(raw: &[u8], crv: &str, is_private: bool) -> Result<Jwk, Error> {
+    if is_private {
+        okp_private(crv, raw).map(Jwk::Okp)
+    } else {
+        OkpOutput::from_public_bytes(crv, raw).map(Jwk::Okp)
+    }
+}
+
+/// The supported curve for the named curve parameter.
+fn curve(oid: Option<ObjectIdentifier>) -> Result<Curve, Error> {
+    match oid {
+        Some(PRIME256V1) => Ok(Curve::P256),
+        Some(SECP384R1) => Ok(Curve::P384),
+        Some(SECP521R1) => Ok(Curve::P521),
+        Some(SECP256K1) => Ok(Curve::Secp256k1),
+        Some(oid) => Err(Error::UnsupportedCurve(oid.to_string())),
+        None => Err(Error::ExplicitCurve),
+    }
+}
+
+/// Convert an elliptic curve private scalar.
+fn ec_private(curve: Curve, d: &[u8]) -> Result<EcOutput, Error> {
+    match curve {
+        Curve::P256 => ec_secret::<p256::NistP256>(curve, d),
+        Curve::P384 => ec_secret::<p384::NistP384>(curve, d),
+        Curve::P521 => ec_secret::<p521::NistP521>(curve, d),
+        Curve::Secp256k1 => ec_secret::<k256::Secp256k1>(curve, d),
+        Curve::BrainpoolP256r1 | Curve::BrainpoolP384r1 | Curve::BrainpoolP512r1 => {
+            Err(Error::UnsupportedCurve(curve.crv_str().to_string()))
+        }
+    }
+}
+
+/// Convert a SEC1 encoded elliptic curve point.
+fn ec_public(curve: Curve, point: &[u8]) -> Result<EcOutput, Error> {
+    match curve {
+        Curve::P256 => ec_point::<p256::NistP256>(curve, point),
+        Curve::P384 => ec_point::<p384::NistP384>(curve, point),
+        Curve::P521 => ec_point::<p521::NistP521>(curve, point),
+        Curve::Secp256k1 => ec_point::<k256::Secp256k1>(curve, point),
+        Curve::BrainpoolP256r1 | Curve::BrainpoolP384r1 | Curve::BrainpoolP512r1 => {
+            Err(Error::UnsupportedCurve(curve.crv_str().to_string()))
+        }
+    }
+}
+
+fn ec_secret<C>(curve: Curve, d: &[u8]) -> Result<EcOutput, Error>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let key = SecretKey::<C>::from_slice(d).parse_err("EC private key")?;
+    let mut output = ec_output(curve, &key.public_key())?;
+
+    let base64_d = Base64UrlUnpadded::encode_string(&Zeroizing::new(key.to_bytes()));
+    output.d = Some(base64_d);
+
+    Ok(output)
+}
+
+fn ec_point<C>(curve: Curve, point: &[u8]) -> Result<EcOutput, Error>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let key = PublicKey::<C>::from_sec1_bytes(point).parse_err("EC public key")?;
+    ec_output(curve, &key)
+}
+
+fn ec_output<C>(curve: Curve, key: &PublicKey<C>) -> Result<EcOutput, Error>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let point = key.to_encoded_point(false);
+    let Coordinates::Uncompressed { x, y } = point.coordinates() else {
+        unreachable!("a public key is never the identity and the point is uncompressed");
+    };
+
+    EcOutput::from_coordinates(curve, x, y)
+}
+
+/// Convert a PKCS#1 RSA private key.
+fn rsa_private(key: &RsaPrivateKey<'_>) -> Result<RsaOutput, Error> {
+    let mut output =
+        RsaOutput::from_components(key.modulus.as_bytes(), key.public_exponent.as_bytes())?;
+
+    let encode =
+        |value: pkcs1::UintRef<'_>| Some(Base64UrlUnpadded::encode_string(value.as_bytes()));
+    output.d = encode(key.private_exponent);
+    output.p = encode(key.prime1);
+    output.q = encode(key.prime2);
+    output.dp = encode(key.exponent1);
+    output.dq = encode(key.exponent2);
+    output.qi = encode(key.coefficient);
+
+    Ok(output)
+}
+
+/// Check if the padded affine coordinates are a point on the curve.
+#[cfg(not(feature = "openssl"))]
+pub(crate) fn is_on_curve(curve: Curve, x: &[u8], y: &[u8]) -> Result<bool, Error> {
+    match curve {
+        Curve::P256 => Ok(on_curve::<p256::NistP256>(x, y)),
+        Curve::P384 => Ok(on_curve::<p384::NistP384>(x, y)),
+        Curve::P521 => Ok(on_curve::<p521::NistP521>(x, y)),
+        Curve::Secp256k1 => Ok(on_curve::<k256::Secp256k1>(x, y)),
+        Curve::BrainpoolP256r1 | Curve::BrainpoolP384r1 | Curve::BrainpoolP512r1 => {
+            Err(Error::UnsupportedCurve(curve.crv_str().to_string()))
+        }
+    }
+}
+
+#[cfg(not(feature = "openssl"))]
+fn on_curve<C>(x: &[u8], y: &[u8]) -> bool
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let point = [&[0x04], x, y].concat();
+    PublicKey::<C>::from_sec1_bytes(&point).is_ok()
+}
+
+/// Check that `n` is `p * q`, that `d` is the private exponent for `e`, and that the CRT members
+/// are derived from them.
+#[cfg(not(feature = "openssl"))]
+pub(crate) fn validate_rsa(output: &RsaOutput) -> Result<(), Error> {
+    let (Some(d), Some(p), Some(q)) = (&output.d, &output.p, &output.q) else {
+        return Ok(());
+    };
+    let decode = |value: &str, member: &str| {
+        decode_member(value, member).map(|bytes| BigUint::from_bytes_be(&Zeroizing::new(bytes)))
+    };
+    let n = decode(&output.n, "n")?;
+    let e = decode(&output.e, "e")?;
+    let d = decode(d, "d")?;
+    let p = decode(p, "p")?;
+    let q = decode(q, "q")?;
+
+    let one = BigUint::from(1_u8);
+    if &p * &q != n || p <= one || q <= one {
+        return Err(Error::InconsistentRsaKey("`n` is not `p * q`"));
+    }
+
+    // `d` may be reduced modulo either Euler's or Carmichael's totient. `d * e` is one modulo
+    // lcm(p - 1, q - 1) exactly when it is one modulo both `p - 1` and `q - 1`, which accepts both.
+    let (p1, q1) = (&p - &one, &q - &one);
+    let de = &d * &e;
+    if &de % &p1 != &one % &p1 || &de % &q1 != &one % &q1 {
+        return Err(Error::InconsistentRsaKey(
+            "`d` is not the private exponent for `e`",
+        ));
+    }
+
+    if let Some(dp) = &output.dp
+        && decode(dp, "dp")? != &d % &p1
+    {
+        return Err(Error::InconsistentRsaKey("`dp` is not `d mod (p - 1)`"));
+    }
+    if let Some(dq) = &output.dq
+        && decode(dq, "dq")? != &d % &q1
+    {
+        return Err(Error::InconsistentRsaKey("`dq` is not `d mod (q - 1)`"));
+    }
+    if let Some(qi) = &output.qi
+        && (decode(qi, "qi")? * &q) % &p != one
+    {
+        return Err(Error::InconsistentRsaKey(
+            "`qi` is not the inverse of `q` mod `p`",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Hash the data with the thumbprint hash.
+#[cfg(not(feature = "openssl"))]
+pub(crate) fn digest(hash: ThumbprintHash, data: &[u8]) -> Vec<u8> {
+    match hash {
+        ThumbprintHash::Sha256 => Sha256::digest(data).to_vec(),
+        ThumbprintHash::Sha384 => Sha384::digest(data).to_vec(),
+        ThumbprintHash::Sha512 => Sha512::digest(data).to_vec(),
+    }
+}
@@ -0,0 +1,193 @@
+//! The shape of the JWK output, as a JSON Schema or as TypeScript types.
+
+use std::fmt::Write;
+
+use serde_json::{Map, Value, json};
+
+/// The type of a JWK member.
+#[derive(Debug, Clone, Copy)]
+enum Type {
+    /// A string, such as a base64url encoded integer.
+    String,
+    /// One of the strings.
+    OneOf(&'static [&'static str]),
+    /// An array of strings.
+    Strings,
+    /// A boolean.
+    Boolean,
+    /// A non-negative integer.
+    Integer,
+}
+
+/// A member of a key's JWK.
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    name: &'static str,
+    type_: Type,
+    required: bool,
+}
+
+const fn required(name: &'static str, type_: Type) -> Member {
+    Member {
+        name,
+        type_,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, type_: Type) -> Member {
+    Member {
+        name,
+        type_,
+        required: false,
+    }
+}
+
+/// The metadata members every key type may have.
+const METADATA: &[Member] = &[
+    optional("alg", Type::String),
+    optional("use", Type::OneOf(&["sig", "enc"])),
+    optional("key_ops", Type::Strings),
+    optional("ext", Type::Boolean),
+    optional("kid", Type::String),
+];
+
+/// The certificate members every key type may have.
+const CERTIFICATE: &[Member] = &[
+    optional("x5c", Type::Strings),
+    optional("x5t", Type::String),
+    optional("x5t#S256", Type::String),
+];
+
+/// Each key type's output name, `kty`, and the members specific to it.
+const KEY_TYPES: &[(&str, &str, &[Member])] = &[
+    (
+        "EcOutput",
+        "EC",
+        &[
+            required("crv", Type::String),
+            required("x", Type::String),
+            required("y", Type::String),
+            optional("d", Type::String),
+            optional("point", Type::String),
+            optional("bits", Type::Integer),
+        ],
+    ),
+    (
+        "RsaOutput",
+        "RSA",
+        &[
+            required("n", Type::String),
+            required("e", Type::String),
+            optional("d", Type::String),
+            optional("p", Type::String),
+            optional("q", Type::String),
+            optional("dp", Type::String),
+            optional("dq", Type::String),
+            optional("qi", Type::String),
+            optional("bits", Type::Integer),
+        ],
+    ),
+    (
+        "OkpOutput",
+        "OKP",
+        &[
+            required("crv", Type::OneOf(&["Ed25519", "Ed448", "X25519", "X448"])),
+            required("x", Type::String),
+            optional("d", Type::String),
+        ],
+    ),
+    ("OctOutput", "oct", &[required("k", Type::String)]),
+];
+
+/// The members of the key type after its `kty`.
+fn members(specific: &'static [Member]) -> impl Iterator<Item = &'static Member> {
+    METADATA.iter().chain(specific).chain(CERTIFICATE)
+}
+
+/// A JSON Schema for a JWK or a JWK Set as output by the converters.
+pub fn json_schema() -> Value {
+    let mut definitions = Map::new();
+    for (name, kty, specific) in KEY_TYPES {
+        let mut properties = Map::from_iter([("kty".to_string(), json!({ "const": kty }))]);
+        let mut required = vec!["kty"];
+        for member in members(specific) {
+            let schema = match member.type_ {
+                Type::String => json!({ "type": "string" }),
+                Type::OneOf(values) => json!({ "enum": values }),
+                Type::Strings => json!({ "type": "array", "items": { "type": "string" } }),
+                Type::Boolean => json!({ "type": "boolean" }),
+                Type::Integer => json!({ "type": "integer", "minimum": 0 }),
+            };
+            properties.insert(member.name.to_string(), schema);
+            if member.required {
+                required.push(member.name);
+            }
+        }
+
+        definitions.insert(
+            name.to_string(),
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }),
+        );
+    }
+
+    let keys: Vec<Value> = KEY_TYPES
+        .iter()
+        .map(|(name, ..)| json!({ "$ref": format!("#/$defs/{name}") }))
+        .collect();
+    definitions.insert("Jwk".to_string(), json!({ "oneOf": keys }));
+    definitions.insert(
+        "JwkSet".to_string(),
+        json!({
+            "type": "object",
+            "properties": { "keys": { "type": "array", "items": { "$ref": "#/$defs/Jwk" } } },
+            "required": ["keys"],
+        }),
+    );
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "JWK",
+        "oneOf": [{ "$ref": "#/$defs/Jwk" }, { "$ref": "#/$defs/JwkSet" }],
+        "$defs": definitions,
+    })
+}
+
+/// TypeScript declarations of a JWK and a JWK Set as output by the converters.
+pub fn typescript() -> String {
+    let mut output = String::new();
+    for (name, kty, specific) in KEY_TYPES {
+        let _ = writeln!(output, "export interface {name} {{\n  kty: \"{kty}\";");
+        for member in members(specific) {
+            let type_ = match member.type_ {
+                Type::String => "string".to_string(),
+                Type::OneOf(values) => values
+                    .iter()
+                    .map(|value| format!("\"{value}\""))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                Type::Strings => "string[]".to_string(),
+                Type::Boolean => "boolean".to_string(),
+                Type::Integer => "number".to_string(),
+            };
+            let name = if member.name.contains('#') {
+                format!("\"{}\"", member.name)
+            } else {
+                member.name.to_string()
+            };
+            let optional = if member.required { "" } else { "?" };
+            let _ = writeln!(output, "  {name}{optional}: {type_};");
+        }
+        output.push_str("}\n\n");
+    }
+
+    let keys: Vec<&str> = KEY_TYPES.iter().map(|(name, ..)| *name).collect();
+    let _ = writeln!(output, "export type Jwk = {};\n", keys.join(" | "));
+    output.push_str("export interface JwkSet {\n  keys: Jwk[];\n}\n");
+
+    output
+}
@@ -0,0 +1,51 @@
+use serde::{
+    Serialize, Serializer,
+    ser::{Error, SerializeMap, SerializeStruct},
+};
+use serde_json::Value;
+
+use crate::{Jwk, JwkSet};
+
+/// The canonical JWK member order: the key type and metadata, then the public members, the
+/// private members, and finally the certificate members.
+pub const MEMBER_ORDER: &[&str] = &[
+    "kty", "crv", "alg", "use", "key_ops", "ext", "kid", "n", "e", "x", "y", "k", "d", "p", "q",
+    "dp", "dq", "qi", "x5c", "x5t", "x5t#S256",
+];
+
+/// Serializes a JWK with its members in [`MEMBER_ORDER`] instead of struct declaration order.
+pub struct SortedJwk<'a>(pub &'a Jwk);
+
+impl Serialize for SortedJwk<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Value::Object(members) = serde_json::to_value(self.0).map_err(S::Error::custom)? else {
+            return Err(S::Error::custom("a JWK must serialize to an object"));
+        };
+
+        let mut map = serializer.serialize_map(Some(members.len()))?;
+        for name in MEMBER_ORDER {
+            if let Some(value) = members.get(*name) {
+                map.serialize_entry(name, value)?;
+            }
+        }
+        for (name, value) in &members {
+            if !MEMBER_ORDER.contains(&name.as_str()) {
+                map.serialize_entry(name, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Serializes a JWK Set with the members of each key in [`MEMBER_ORDER`].
+pub struct SortedJwkSet<'a>(pub &'a JwkSet);
+
+impl Serialize for SortedJwkSet<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let keys: Vec<SortedJwk<'_>> = self.0.keys.iter().map(SortedJwk).collect();
+
+        let mut set = serializer.serialize_struct("JwkSet", 1)?;
+        set.serialize_field("keys", &keys)?;
+        set.end()
+    }
+}
@@ -0,0 +1,86 @@
+use base64ct::{Base64, Encoding};
+
+use crate::{Curve, EcOutput, Jwk, OkpOutput, RsaOutput, error::Error};
+
+/// Convert an OpenSSH public key line, `<type> <base64> [comment]`, to its JWK.
+pub fn ssh_to_jwk(line: &[u8]) -> Result<Jwk, Error> {
+    let line = String::from_utf8_lossy(line);
+    let mut fields = line.split_whitespace();
+    let (Some(key_type), Some(encoded)) = (fields.next(), fields.next()) else {
+        return Err(Error::InvalidSshKey("expected a key type and base64 key"));
+    };
+
+    let blob = Base64::decode_vec(encoded).map_err(|source| Error::Encoding {
+        name: "SSH public key".to_string(),
+        source,
+    })?;
+    let mut reader = Reader(&blob);
+    if reader.string()? != key_type.as_bytes() {
+        return Err(Error::InvalidSshKey(
+            "the key type does not match the encoded key",
+        ));
+    }
+
+    match key_type {
+        "ssh-ed25519" => {
+            let key = reader.string()?;
+            if key.len() != 32 {
+                return Err(Error::InvalidSshKey("an Ed25519 key must be 32 bytes"));
+            }
+            Ok(Jwk::Okp(OkpOutput::from_public_bytes("Ed25519", key)?))
+        }
+        "ssh-rsa" => {
+            // The exponent comes before the modulus in the wire format.
+            let e = reader.string()?;
+            let n = reader.string()?;
+            Ok(Jwk::Rsa(RsaOutput::from_components(n, e)?))
+        }
+        "ecdsa-sha2-nistp256" => ecdsa(Curve::P256, b"nistp256", &mut reader),
+        "ecdsa-sha2-nistp384" => ecdsa(Curve::P384, b"nistp384", &mut reader),
+        "ecdsa-sha2-nistp521" => ecdsa(Curve::P521, b"nistp521", &mut reader),
+        key_type => Err(Error::UnsupportedKeyType(key_type.to_string())),
+    }
+}
+
+/// Convert the curve name and uncompressed SEC1 point of an ECDSA key, the curve name must match
+/// the key type.
+fn ecdsa(curve: Curve, curve_name: &[u8], reader: &mut Reader<'_>) -> Result<Jwk, Error> {
+    if reader.string()? != curve_name {
+        return Err(Error::InvalidSshKey(
+            "the curve name does not match the key type",
+        ));
+    }
+    let point = reader.string()?;
+
+    let field_size = usize::try_from(curve.field_size()).unwrap_or_default();
+    let coordinates = match point.split_first() {
+        Some((0x04, coordinates)) if coordinates.len() == field_size * 2 => coordinates,
+        _ => {
+            return Err(Error::InvalidSshKey("expected an uncompressed point"));
+        }
+    };
+    let (x, y) = coordinates.split_at(field_size);
+
+    Ok(Jwk::Ec(EcOutput::from_coordinates(curve, x, y)?))
+}
+
+/// Reads the length prefixed strings of the SSH wire format.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn string(&mut self) -> Result<&'a [u8], Error> {
+        let value = self
+            .0
+            .split_first_chunk::<4>()
+            .and_then(|(length, rest)| {
+                let length = usize::try_from(u32::from_be_bytes(*length)).ok()?;
+                rest.split_at_checked(length)
+            })
+            .map(|(value, rest)| {
+                self.0 = rest;
+                value
+            });
+
+        value.ok_or(Error::InvalidSshKey("the encoded key is truncated"))
+    }
+}
@@ -0,0 +1,194 @@
+//! Every key type and curve maps to its own `alg` and rejects the others.
+
+use std::fs;
+
+use pem_params::{
+    Curve, Error,
+    alg::{
+        default_alg, default_okp_alg, use_for_alg, validate_alg, validate_okp_alg, validate_rsa_alg,
+    },
+    pem_to_jwk,
+};
+
+const EC: &[(Curve, Option<&str>)] = &[
+    (Curve::P256, Some("ES256")),
+    (Curve::P384, Some("ES384")),
+    (Curve::P521, Some("ES512")),
+    (Curve::Secp256k1, Some("ES256K")),
+    (Curve::BrainpoolP256r1, None),
+    (Curve::BrainpoolP384r1, None),
+    (Curve::BrainpoolP512r1, None),
+];
+
+const OKP: &[(&str, &str)] = &[
+    ("Ed25519", "EdDSA"),
+    ("Ed448", "EdDSA"),
+    ("X25519", "ECDH-ES"),
+    ("X448", "ECDH-ES"),
+];
+
+const SIGNATURE_ALGS: &[&str] = &["ES256", "ES384", "ES512", "ES256K", "EdDSA", "RS256"];
+
+#[test]
+fn ec_pairings() {
+    for &(curve, alg) in EC {
+        assert_eq!(default_alg(curve), alg, "{curve:?}");
+
+        for signature_alg in SIGNATURE_ALGS {
+            assert_eq!(
+                validate_alg(signature_alg, curve),
+                alg == Some(signature_alg),
+                "{signature_alg} with {curve:?}"
+            );
+        }
+        assert!(validate_alg("ECDH-ES+A128KW", curve), "{curve:?}");
+    }
+}
+
+#[test]
+fn okp_pairings() {
+    for &(crv, alg) in OKP {
+        assert_eq!(default_okp_alg(crv), Some(alg), "{crv}");
+        assert!(validate_okp_alg(alg, crv), "{alg} with {crv}");
+
+        for signature_alg in SIGNATURE_ALGS {
+            assert_eq!(
+                validate_okp_alg(signature_alg, crv),
+                alg == *signature_alg,
+                "{signature_alg} with {crv}"
+            );
+        }
+    }
+
+    assert_eq!(default_okp_alg("P-256"), None);
+    assert!(!validate_okp_alg("EdDSA", "P-256"));
+}
+
+#[test]
+fn rsa_algs() {
+    for alg in [
+        "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "RSA-OAEP",
+    ] {
+        assert!(validate_rsa_alg(alg), "{alg}");
+    }
+    for alg in ["ES256", "EdDSA", "ECDH-ES", "HS256"] {
+        assert!(!validate_rsa_alg(alg), "{alg}");
+    }
+}
+
+#[test]
+fn rsa_default_algs() {
+    let rsa = pem_to_jwk(&fs::read("tests/fixtures/rsa.pub.pem").unwrap(), false).unwrap();
+    let pss = pem_to_jwk(&fs::read("tests/fixtures/rsa-pss.pem").unwrap(), true).unwrap();
+
+    assert_eq!(serde_json::to_value(&rsa).unwrap()["alg"], "RS256");
+    assert_eq!(serde_json::to_value(&pss).unwrap()["alg"], "PS384");
+}
+
+#[test]
+fn set_alg_rejects_mismatched_kty() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+
+    assert!(matches!(
+        jwk.set_alg("ES256".to_string()),
+        Err(Error::InvalidAlg { kty: "RSA", .. })
+    ));
+    assert!(jwk.set_alg("PS512".to_string()).is_ok());
+}
+
+#[test]
+fn set_alg_rejects_non_pss_for_pss_key() {
+    let pem = fs::read("tests/fixtures/rsa-pss.pem").unwrap();
+    let mut jwk = pem_to_jwk(&pem, true).unwrap();
+
+    for alg in ["RS256", "RSA-OAEP", "RSA-OAEP-256", "RSA1_5"] {
+        assert!(
+            matches!(
+                jwk.set_alg(alg.to_string()),
+                Err(Error::InvalidAlg { kty: "RSA", .. })
+            ),
+            "{alg}"
+        );
+    }
+    assert!(jwk.set_alg("PS384".to_string()).is_ok());
+}
+
+#[test]
+fn set_alg_rejects_mismatched_curve() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+
+    assert!(matches!(
+        jwk.set_alg("ES384".to_string()),
+        Err(Error::InvalidAlg { kty: "EC", .. })
+    ));
+    assert!(jwk.set_alg("ECDH-ES".to_string()).is_ok());
+}
+
+#[test]
+fn set_alg_rejects_eddsa_on_key_agreement_curve() {
+    for (name, accepts) in [
+        ("ed25519.pub", true),
+        ("ed448.pub", true),
+        ("x25519.pub", false),
+    ] {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        let mut jwk = pem_to_jwk(&pem, false).unwrap();
+
+        let result = jwk.set_alg("EdDSA".to_string());
+
+        if accepts {
+            assert!(result.is_ok(), "{name}");
+        } else {
+            assert!(
+                matches!(result, Err(Error::InvalidAlg { kty: "OKP", .. })),
+                "{name}"
+            );
+        }
+    }
+}
+
+#[test]
+fn clear_alg_omits_member() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+
+    jwk.clear_alg();
+
+    let value = serde_json::to_value(&jwk).unwrap();
+    assert!(value.get("alg").is_none());
+    assert_eq!(value["kty"], "RSA");
+}
+
+#[test]
+fn use_from_alg() {
+    for alg in ["ES256", "ES256K", "EdDSA", "RS256", "PS384", "HS512"] {
+        assert_eq!(use_for_alg(alg), Some("sig"), "{alg}");
+    }
+    for alg in [
+        "ECDH-ES",
+        "ECDH-ES+A256KW",
+        "RSA-OAEP",
+        "RSA-OAEP-256",
+        "RSA1_5",
+    ] {
+        assert_eq!(use_for_alg(alg), Some("enc"), "{alg}");
+    }
+    assert_eq!(use_for_alg("none"), None);
+}
+
+#[test]
+fn infer_use_keeps_existing_use() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+    assert!(serde_json::to_value(&jwk).unwrap().get("use").is_none());
+
+    jwk.set_alg("ECDH-ES".to_string()).unwrap();
+    jwk.infer_use();
+    assert_eq!(serde_json::to_value(&jwk).unwrap()["use"], "enc");
+
+    jwk.set_use("sig".to_string());
+    jwk.infer_use();
+    assert_eq!(serde_json::to_value(&jwk).unwrap()["use"], "sig");
+}
@@ -0,0 +1,49 @@
+//! The builder sets the same members as the `Jwk` setters and checks the `alg` when building.
+
+use std::fs;
+
+use pem_params::{Error, pem_to_jwk};
+use serde_json::json;
+
+#[test]
+fn sets_metadata() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let jwk = pem_to_jwk(&pem, false)
+        .unwrap()
+        .builder()
+        .alg("ECDH-ES")
+        .use_("enc")
+        .kid("key-1")
+        .key_ops(["deriveKey", "deriveBits"])
+        .build()
+        .unwrap();
+
+    let value = serde_json::to_value(&jwk).unwrap();
+    assert_eq!(value["alg"], "ECDH-ES");
+    assert_eq!(value["use"], "enc");
+    assert_eq!(value["kid"], "key-1");
+    assert_eq!(value["key_ops"], json!(["deriveKey", "deriveBits"]));
+}
+
+#[test]
+fn keeps_derived_members() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let converted = pem_to_jwk(&pem, false).unwrap();
+    let expected = serde_json::to_value(&converted).unwrap();
+
+    let jwk = converted.builder().build().unwrap();
+
+    assert_eq!(serde_json::to_value(&jwk).unwrap(), expected);
+}
+
+#[test]
+fn rejects_mismatched_alg() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let result = pem_to_jwk(&pem, false)
+        .unwrap()
+        .builder()
+        .alg("ES256")
+        .build();
+
+    assert!(matches!(result, Err(Error::InvalidAlg { kty: "RSA", .. })));
+}
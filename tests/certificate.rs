@@ -0,0 +1,119 @@
+//! The `use` of a certificate key is derived from its keyUsage or extKeyUsage extension, and `x5c`
+//! entries are standard padded base64. Certificates bundled with a key are attached to it.
+
+#![cfg(feature = "openssl")]
+
+use std::fs;
+
+use base64ct::{Base64, Base64UrlUnpadded, Encoding};
+use openssl::x509::X509;
+use pem_params::{
+    Jwk, attach_certificates, certificate_pem_to_jwk, pem_to_jwk, split_pem, x5c_from_pem,
+};
+
+fn use_of(jwk: &Jwk) -> Option<&str> {
+    match jwk {
+        Jwk::Ec(output) => output.use_.as_deref(),
+        _ => panic!("expected an EC JWK"),
+    }
+}
+
+fn certificate_use(name: &str) -> Option<String> {
+    let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+    use_of(&certificate_pem_to_jwk(&pem).unwrap()).map(str::to_string)
+}
+
+#[test]
+fn digital_signature_is_sig() {
+    assert_eq!(certificate_use("cert-sig").as_deref(), Some("sig"));
+}
+
+#[test]
+fn key_encipherment_is_enc() {
+    assert_eq!(certificate_use("cert-enc").as_deref(), Some("enc"));
+}
+
+#[test]
+fn both_usages_leave_use_unset() {
+    assert_eq!(certificate_use("cert-both"), None);
+}
+
+#[test]
+fn signing_extended_usage_is_sig() {
+    assert_eq!(certificate_use("cert-eku").as_deref(), Some("sig"));
+}
+
+#[test]
+fn x5c_sets_use_from_leaf() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let x5c = x5c_from_pem(&fs::read("tests/fixtures/cert-enc.pem").unwrap()).unwrap();
+
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+    jwk.set_x5c(x5c).unwrap();
+
+    assert_eq!(use_of(&jwk), Some("enc"));
+}
+
+#[test]
+fn x5t_is_certificate_fingerprint() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let x5c = x5c_from_pem(&fs::read("tests/fixtures/cert-sig.pem").unwrap()).unwrap();
+
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+    jwk.set_x5c(x5c).unwrap();
+
+    // The fingerprints from `openssl x509 -fingerprint -sha1` and `-sha256`, base64url encoded.
+    let Jwk::Ec(output) = &jwk else {
+        panic!("expected an EC JWK");
+    };
+    assert_eq!(output.x5t.as_deref(), Some("5rJX3VbEJebjAqgA3Yhwg5AL0dc"));
+    assert_eq!(
+        output.x5t_s256.as_deref(),
+        Some("UNOLlTJ6TOLk6xf6WAhNRXq5OFUBsN8Ka0fQaZb6StE")
+    );
+    assert_ne!(output.x5t_s256, output.kid);
+}
+
+#[test]
+fn x5c_entry_is_standard_base64() {
+    let pem = fs::read("tests/fixtures/cert-sig.pem").unwrap();
+    let der = X509::from_pem(&pem).unwrap().to_der().unwrap();
+
+    let x5c = x5c_from_pem(&pem).unwrap();
+
+    assert_eq!(Base64::decode_vec(&x5c[0]).unwrap(), der);
+    assert!(Base64UrlUnpadded::decode_vec(&x5c[0]).is_err());
+}
+
+#[test]
+fn bundled_certificate_is_attached() {
+    let pem = fs::read("tests/fixtures/ec-p256.bundle.pem").unwrap();
+    let blocks = split_pem(&pem);
+    let mut keys = vec![pem_to_jwk(blocks[0], true).unwrap()];
+
+    let unmatched = attach_certificates(&mut keys, &blocks[1..]).unwrap();
+
+    let unmatched: Vec<_> = unmatched.iter().map(|(index, _)| *index).collect();
+    assert_eq!(unmatched, [1]);
+    let Jwk::Ec(output) = &keys[0] else {
+        panic!("expected an EC JWK");
+    };
+    let leaf = X509::from_pem(blocks[1]).unwrap().to_der().unwrap();
+    assert_eq!(output.x5c, Some(vec![Base64::encode_string(&leaf)]));
+    assert!(output.x5t_s256.is_some());
+}
+
+#[test]
+fn broken_bundled_certificate_is_skipped() {
+    let pem = fs::read("tests/fixtures/ec-p256.bundle-broken.pem").unwrap();
+    let blocks = split_pem(&pem);
+    let mut keys = vec![pem_to_jwk(blocks[0], true).unwrap()];
+
+    let unmatched = attach_certificates(&mut keys, &blocks[1..]).unwrap();
+
+    assert!(matches!(unmatched.as_slice(), [(0, Some(_))]));
+    let Jwk::Ec(output) = &keys[0] else {
+        panic!("expected an EC JWK");
+    };
+    assert!(output.x5c.is_some());
+}
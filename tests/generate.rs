@@ -0,0 +1,37 @@
+//! Generated keys convert back to the same JWK from their PEM.
+
+#![cfg(feature = "openssl")]
+
+use pem_params::{KeySpec, generate, pem_to_jwk};
+
+fn assert_round_trips(spec: KeySpec<'_>) {
+    let (jwk, pem) = generate(spec).unwrap();
+
+    let converted = pem_to_jwk(&pem, true).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&converted).unwrap(),
+        serde_json::to_value(&jwk).unwrap()
+    );
+}
+
+#[test]
+fn ec_p256() {
+    assert_round_trips(KeySpec::Ec("P-256"));
+}
+
+#[test]
+fn rsa() {
+    assert_round_trips(KeySpec::Rsa(2048));
+}
+
+#[test]
+#[cfg(not(feature = "rustcrypto"))]
+fn okp_ed25519() {
+    assert_round_trips(KeySpec::Okp("Ed25519"));
+}
+
+#[test]
+fn rejects_okp_curve_for_ec() {
+    assert!(generate(KeySpec::Ec("Ed25519")).is_err());
+}
@@ -0,0 +1,39 @@
+//! The JWK types display as their pretty printed JSON and parse from it.
+
+use std::fs;
+
+use pem_params::{EcOutput, Error, Jwk, RsaOutput, pem_to_json};
+
+#[test]
+fn display_matches_pem_to_json() {
+    let pem = fs::read("tests/fixtures/ec-p256.pem").unwrap();
+    let json = pem_to_json(&pem, true).unwrap();
+
+    let jwk: Jwk = json.parse().unwrap();
+
+    assert_eq!(jwk.to_string(), json);
+}
+
+#[test]
+fn parse_typed_outputs() {
+    let ec = fs::read_to_string("tests/fixtures/ec-p256.pub.json").unwrap();
+    let rsa = fs::read_to_string("tests/fixtures/rsa.pub.json").unwrap();
+
+    let ec: EcOutput = ec.parse().unwrap();
+    let rsa: RsaOutput = rsa.parse().unwrap();
+
+    assert_eq!(ec.crv, "P-256");
+    assert_eq!(rsa.e, "AQAB");
+    assert_eq!(ec.to_string().parse::<EcOutput>().unwrap().x, ec.x);
+}
+
+#[test]
+fn rejects_invalid_json() {
+    assert!(matches!(
+        "{".parse::<Jwk>(),
+        Err(Error::Parse {
+            input: "JWK JSON",
+            ..
+        })
+    ));
+}
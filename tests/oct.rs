@@ -0,0 +1,18 @@
+//! A raw secret becomes an `oct` key, and an empty secret is rejected.
+
+use pem_params::{Error, Jwk, secret_to_jwk};
+
+#[test]
+fn secret() {
+    let Jwk::Oct(output) = secret_to_jwk(b"secret").unwrap() else {
+        panic!("expected an oct JWK");
+    };
+
+    assert_eq!(output.k, "c2VjcmV0");
+    assert_eq!(output.alg, "HS256");
+}
+
+#[test]
+fn rejects_empty_secret() {
+    assert!(matches!(secret_to_jwk(b""), Err(Error::EmptySecret)));
+}
@@ -0,0 +1,449 @@
+//! `pem_to_jwk` must produce the same JWK whichever backend is enabled, run with and without the
+//! `rustcrypto` feature. The Brainpool curves and private Ed448 keys are only supported by OpenSSL.
+
+use std::fs;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+#[cfg(feature = "openssl")]
+use openssl::{
+    bn::{BigNum, BigNumContext},
+    ec::{EcGroup, EcKey, EcPoint, EcPointRef, PointConversionForm},
+};
+#[cfg(feature = "openssl")]
+use pem_params::ec_to_jwk_value;
+use pem_params::{Error, Jwk, der_to_jwk, pem_to_json, pem_to_jwk};
+use serde_json::Value;
+
+fn assert_converts(name: &str, is_private: bool) {
+    assert_converts_as(name, name, is_private);
+}
+
+fn assert_converts_as(name: &str, expected: &str, is_private: bool) {
+    let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+    let expected: Value =
+        serde_json::from_slice(&fs::read(format!("tests/fixtures/{expected}.json")).unwrap())
+            .unwrap();
+
+    let jwk = pem_to_jwk(&pem, is_private).unwrap();
+
+    assert_eq!(serde_json::to_value(&jwk).unwrap(), expected);
+}
+
+#[test]
+fn ec_p256_private() {
+    assert_converts("ec-p256", true);
+}
+
+#[test]
+fn ec_p256_public() {
+    assert_converts("ec-p256.pub", false);
+}
+
+#[test]
+fn ec_p384_private() {
+    assert_converts("ec-p384", true);
+}
+
+#[test]
+fn ec_p384_public() {
+    assert_converts("ec-p384.pub", false);
+}
+
+#[test]
+fn ec_p521_private() {
+    assert_converts("ec-p521", true);
+}
+
+#[test]
+fn ec_p521_public() {
+    assert_converts("ec-p521.pub", false);
+}
+
+#[test]
+fn ec_crv_and_alg() {
+    for (name, is_private, crv, alg) in [
+        ("ec-p256", true, "P-256", "ES256"),
+        ("ec-p384", true, "P-384", "ES384"),
+        ("ec-p384.pub", false, "P-384", "ES384"),
+        ("ec-p521", true, "P-521", "ES512"),
+        ("ec-p521.pub", false, "P-521", "ES512"),
+    ] {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        let json = serde_json::to_value(pem_to_jwk(&pem, is_private).unwrap()).unwrap();
+
+        assert_eq!(json["crv"], crv, "{name}");
+        assert_eq!(json["alg"], alg, "{name}");
+    }
+}
+
+#[test]
+fn ec_secp256k1_private() {
+    assert_converts("ec-secp256k1", true);
+}
+
+#[test]
+#[cfg(not(feature = "rustcrypto"))]
+fn ec_brainpool_private() {
+    assert_converts("ec-bp256", true);
+    assert_converts("ec-bp384", true);
+    assert_converts("ec-bp512", true);
+}
+
+#[test]
+fn ec_sec1_private() {
+    assert_converts("ec-sec1", true);
+}
+
+#[test]
+fn ec_sec1_matches_pkcs8() {
+    assert_converts_as("ec-p256.sec1", "ec-p256", true);
+}
+
+#[test]
+fn ec_p256_padded_private() {
+    assert_converts("ec-p256-padded", true);
+}
+
+#[test]
+fn ec_p256_padded_public() {
+    assert_converts("ec-p256-padded.pub", false);
+}
+
+#[test]
+fn ec_coordinates_keep_leading_zero() {
+    // Both coordinates of this key start with a zero byte, which an unpadded big number drops.
+    let pem = fs::read("tests/fixtures/ec-p256-padded.pub.pem").unwrap();
+    let Jwk::Ec(jwk) = pem_to_jwk(&pem, false).unwrap() else {
+        panic!("expected an EC JWK");
+    };
+
+    for (name, value) in [("x", &jwk.x), ("y", &jwk.y)] {
+        let value = Base64UrlUnpadded::decode_vec(value).unwrap();
+        assert_eq!(value.len(), 32, "{name}");
+        assert_eq!(value[0], 0, "{name}");
+    }
+}
+
+#[test]
+fn crlf_line_endings() {
+    assert_converts_as("ec-p256.crlf", "ec-p256", true);
+}
+
+#[test]
+fn byte_order_mark() {
+    assert_converts_as("ec-p256.bom", "ec-p256", true);
+}
+
+#[test]
+fn rsa_private() {
+    assert_converts("rsa", true);
+}
+
+#[test]
+fn rsa_pkcs1_private() {
+    assert_converts("rsa-pkcs1", true);
+}
+
+#[test]
+fn rsa_small_exponent() {
+    // e = 3 is the single byte 0x03, and 65537 the three bytes 0x01 0x00 0x01.
+    assert_converts("rsa-e3", true);
+
+    let json = |name: &str, is_private: bool| {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        serde_json::to_value(pem_to_jwk(&pem, is_private).unwrap()).unwrap()
+    };
+    assert_eq!(json("rsa-e3", true)["e"], "Aw");
+    assert_eq!(json("rsa.pub", false)["e"], "AQAB");
+}
+
+#[test]
+fn rsa_public() {
+    assert_converts("rsa.pub", false);
+}
+
+#[test]
+fn rsa_pss_private() {
+    assert_converts("rsa-pss", true);
+}
+
+#[test]
+fn ed25519_private() {
+    assert_converts("ed25519", true);
+}
+
+#[test]
+fn ed25519_public() {
+    assert_converts("ed25519.pub", false);
+}
+
+#[test]
+#[cfg(not(feature = "rustcrypto"))]
+fn ed448_private() {
+    assert_converts("ed448", true);
+
+    // Ed448 keys are 57 bytes, one more than the 56 byte field elements of X448.
+    let pem = fs::read("tests/fixtures/ed448.pem").unwrap();
+    let Jwk::Okp(jwk) = pem_to_jwk(&pem, true).unwrap() else {
+        panic!("expected an OKP JWK");
+    };
+    assert_eq!(Base64UrlUnpadded::decode_vec(&jwk.x).unwrap().len(), 57);
+    assert_eq!(
+        Base64UrlUnpadded::decode_vec(jwk.d.as_deref().unwrap())
+            .unwrap()
+            .len(),
+        57
+    );
+}
+
+#[test]
+fn ed448_public() {
+    assert_converts("ed448.pub", false);
+}
+
+#[test]
+fn x25519_private() {
+    assert_converts("x25519", true);
+}
+
+#[test]
+fn x25519_public() {
+    assert_converts("x25519.pub", false);
+}
+
+#[test]
+fn eddsa_curves() {
+    // Both signing curves use EdDSA, the curve and the length of `x` tell them apart.
+    for (name, crv, length) in [("ed25519.pub", "Ed25519", 32), ("ed448.pub", "Ed448", 57)] {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        let json = serde_json::to_value(pem_to_jwk(&pem, false).unwrap()).unwrap();
+
+        assert_eq!(json["alg"], "EdDSA", "{name}");
+        assert_eq!(json["crv"], crv, "{name}");
+        assert_eq!(
+            Base64UrlUnpadded::decode_vec(json["x"].as_str().unwrap())
+                .unwrap()
+                .len(),
+            length,
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn pem_to_json_matches_jwk() {
+    let pem = fs::read("tests/fixtures/ec-p256.pem").unwrap();
+    let expected: Value =
+        serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.json").unwrap()).unwrap();
+
+    let json = pem_to_json(&pem, true).unwrap();
+
+    assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), expected);
+}
+
+#[test]
+fn pem_to_json_is_byte_for_byte() {
+    for name in ["ec-p256", "ec-p256-padded", "ec-p384", "rsa"] {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        let expected = fs::read_to_string(format!("tests/fixtures/{name}.json")).unwrap();
+
+        let json = pem_to_json(&pem, true).unwrap();
+
+        assert_eq!(format!("{json}\n"), expected, "{name} differs");
+    }
+}
+
+#[test]
+#[cfg(feature = "openssl")]
+fn ec_to_jwk_value_matches_public_jwk() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let expected: Value =
+        serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.pub.json").unwrap()).unwrap();
+
+    let key = EcKey::public_key_from_pem(&pem).unwrap();
+    let value = ec_to_jwk_value(&key).unwrap();
+
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn rsa_modulus_is_unsigned() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+
+    let Jwk::Rsa(jwk) = pem_to_jwk(&pem, false).unwrap() else {
+        panic!("expected an RSA JWK");
+    };
+    let n = Base64UrlUnpadded::decode_vec(&jwk.n).unwrap();
+    let e = Base64UrlUnpadded::decode_vec(&jwk.e).unwrap();
+
+    assert_eq!(n.len(), 256);
+    assert!(n[0] & 0x80 != 0);
+    assert_eq!(e, [0x01, 0x00, 0x01]);
+}
+
+#[test]
+fn private_key_as_public() {
+    let pem = fs::read("tests/fixtures/ec-p256.pem").unwrap();
+
+    assert!(matches!(
+        pem_to_jwk(&pem, false),
+        Err(Error::UnexpectedKeyKind {
+            expected: "public",
+            found: "private"
+        })
+    ));
+}
+
+#[test]
+fn public_key_as_private() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let der = fs::read("tests/fixtures/rsa.pub.der").unwrap();
+
+    for result in [pem_to_jwk(&pem, true), der_to_jwk(&der, true)] {
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedKeyKind {
+                expected: "private",
+                found: "public"
+            })
+        ));
+    }
+}
+
+#[test]
+fn der_public_key_info() {
+    for name in ["ec-p256.pub", "rsa.pub"] {
+        let der = fs::read(format!("tests/fixtures/{name}.der")).unwrap();
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(der_to_jwk(&der, false).unwrap()).unwrap(),
+            serde_json::to_value(pem_to_jwk(&pem, false).unwrap()).unwrap(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "openssl")]
+fn include_point() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let key = EcKey::public_key_from_pem(&pem).unwrap();
+    let mut ctx = BigNumContext::new().unwrap();
+    let expected = key
+        .public_key()
+        .to_bytes(key.group(), PointConversionForm::COMPRESSED, &mut ctx)
+        .unwrap();
+
+    let mut jwk = pem_to_jwk(&pem, false).unwrap();
+    jwk.include_point().unwrap();
+
+    let json = serde_json::to_value(&jwk).unwrap();
+    assert_eq!(
+        json["point"].as_str().unwrap(),
+        Base64UrlUnpadded::encode_string(&expected)
+    );
+}
+
+#[test]
+fn include_bits() {
+    for (name, is_private, bits) in [
+        ("rsa.pub", false, 2048),
+        ("rsa-1024.pub", false, 1024),
+        ("ec-p256.pub", false, 256),
+        ("ec-p521", true, 521),
+    ] {
+        let pem = fs::read(format!("tests/fixtures/{name}.pem")).unwrap();
+        let mut jwk = pem_to_jwk(&pem, is_private).unwrap();
+        assert!(serde_json::to_value(&jwk).unwrap().get("bits").is_none());
+
+        jwk.include_bits().unwrap();
+
+        assert_eq!(serde_json::to_value(&jwk).unwrap()["bits"], bits, "{name}");
+    }
+}
+
+#[test]
+fn coordinates_hex() {
+    let pem = fs::read("tests/fixtures/ec-p256-padded.pem").unwrap();
+    let jwk = pem_to_jwk(&pem, true).unwrap();
+
+    assert_eq!(
+        jwk.coordinates_hex().unwrap(),
+        "x: 000e7e93f77fa30a96496d572f128e13048b59c60fdfa63c66a71694fffe0fb3\n\
+         y: 2ee9a1c604f0feb6980cda3baad9148733170e7b9c58c9cc9a3a16b1846dc3c3\n\
+         d: e51024dfa475181c2e5d314a76b2379685aaa1a0ffdeec4f60572c7934c6ed53\n"
+    );
+
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+    let jwk = pem_to_jwk(&pem, false).unwrap();
+    assert!(matches!(
+        jwk.coordinates_hex(),
+        Err(Error::UnsupportedKeyType(_))
+    ));
+}
+
+#[test]
+fn rejects_coordinate_larger_than_field() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let Jwk::Ec(mut output) = pem_to_jwk(&pem, false).unwrap() else {
+        panic!("expected an EC JWK");
+    };
+    output.x = Base64UrlUnpadded::encode_string(&[0xff; 33]);
+
+    assert!(matches!(
+        output.coordinates_hex(),
+        Err(Error::CoordinateTooLarge { member, curve }) if member == "x" && curve == "P-256"
+    ));
+}
+
+#[test]
+#[cfg(not(feature = "rustcrypto"))]
+fn ec_explicit_parameters() {
+    assert_converts_as("ec-p256.explicit", "ec-p256", true);
+    assert_converts_as("ec-p256.explicit.pub", "ec-p256.pub", false);
+}
+
+#[test]
+#[cfg(feature = "openssl")]
+fn ec_explicit_parameters_match_named_curve() {
+    let pem = fs::read("tests/fixtures/ec-p256.pub.pem").unwrap();
+    let named = EcKey::public_key_from_pem(&pem).unwrap();
+    let expected = ec_to_jwk_value(&named).unwrap();
+
+    // A group built from its components has no curve name, and its points must be re-encoded as
+    // the named group uses a different implementation.
+    let mut ctx = BigNumContext::new().unwrap();
+    let group = named.group();
+    let encode = |point: &EcPointRef, ctx: &mut BigNumContext| {
+        point
+            .to_bytes(group, PointConversionForm::UNCOMPRESSED, ctx)
+            .unwrap()
+    };
+    let [mut p, mut a, mut b, mut order, mut cofactor] =
+        std::array::from_fn(|_| BigNum::new().unwrap());
+    group
+        .components_gfp(&mut p, &mut a, &mut b, &mut ctx)
+        .unwrap();
+    group.order(&mut order, &mut ctx).unwrap();
+    group.cofactor(&mut cofactor, &mut ctx).unwrap();
+
+    let mut explicit = EcGroup::from_components(p, a, b, &mut ctx).unwrap();
+    let generator = encode(group.generator_opt().unwrap(), &mut ctx);
+    let generator = EcPoint::from_bytes(&explicit, &generator, &mut ctx).unwrap();
+    explicit.set_generator(generator, order, cofactor).unwrap();
+    assert!(explicit.curve_name().is_none());
+
+    let point = encode(named.public_key(), &mut ctx);
+    let point = EcPoint::from_bytes(&explicit, &point, &mut ctx).unwrap();
+    let key = EcKey::from_public_key(&explicit, &point).unwrap();
+
+    assert_eq!(ec_to_jwk_value(&key).unwrap(), expected);
+}
+
+#[test]
+fn ec_explicit_parameters_unknown_curve() {
+    let pem = fs::read("tests/fixtures/ec-custom.explicit.pub.pem").unwrap();
+
+    assert!(matches!(pem_to_jwk(&pem, false), Err(Error::ExplicitCurve)));
+}
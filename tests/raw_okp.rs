@@ -0,0 +1,57 @@
+//! Raw OKP keys convert to the same JWK as their PEM.
+
+use std::fs;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use pem_params::{Error, raw_okp_to_jwk};
+#[cfg(feature = "openssl")]
+use pem_params::{KeySpec, generate};
+use serde_json::Value;
+
+#[test]
+fn ed25519_public() {
+    let expected: Value =
+        serde_json::from_slice(&fs::read("tests/fixtures/ed25519.pub.json").unwrap()).unwrap();
+    let raw = Base64UrlUnpadded::decode_vec(expected["x"].as_str().unwrap()).unwrap();
+
+    let jwk = raw_okp_to_jwk(&raw, "Ed25519", false).unwrap();
+
+    assert_eq!(serde_json::to_value(&jwk).unwrap(), expected);
+}
+
+#[test]
+fn x25519_private() {
+    let expected: Value =
+        serde_json::from_slice(&fs::read("tests/fixtures/x25519.json").unwrap()).unwrap();
+    let raw = Base64UrlUnpadded::decode_vec(expected["d"].as_str().unwrap()).unwrap();
+
+    let jwk = raw_okp_to_jwk(&raw, "X25519", true).unwrap();
+
+    assert_eq!(serde_json::to_value(&jwk).unwrap(), expected);
+}
+
+#[test]
+#[cfg(feature = "openssl")]
+fn private() {
+    for crv in ["Ed25519", "Ed448", "X25519", "X448"] {
+        let (generated, _) = generate(KeySpec::Okp(crv)).unwrap();
+        let expected = serde_json::to_value(&generated).unwrap();
+        let raw = Base64UrlUnpadded::decode_vec(expected["d"].as_str().unwrap()).unwrap();
+
+        let jwk = raw_okp_to_jwk(&raw, crv, true).unwrap();
+
+        assert_eq!(serde_json::to_value(&jwk).unwrap(), expected, "{crv}");
+    }
+}
+
+#[test]
+fn rejects_wrong_length() {
+    assert!(matches!(
+        raw_okp_to_jwk(&[0; 32], "Ed448", false),
+        Err(Error::InvalidKeyLength {
+            expected: 57,
+            length: 32,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,67 @@
+//! The schema describes every member the converters output.
+
+use std::fs;
+
+use pem_params::{
+    Jwk,
+    schema::{json_schema, typescript},
+};
+use serde_json::Value;
+
+fn definition(schema: &Value, kty: &str) -> Value {
+    schema["$defs"]["Jwk"]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|reference| {
+            let name = reference["$ref"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("#/$defs/");
+            schema["$defs"][name].clone()
+        })
+        .find(|definition| definition["properties"]["kty"]["const"] == kty)
+        .unwrap()
+}
+
+#[test]
+fn describes_fixtures() {
+    let schema = json_schema();
+
+    for name in ["ec-p256", "ec-p256.pub", "rsa", "rsa.pub", "ed25519.pub"] {
+        let mut jwk: Jwk =
+            serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.json")).unwrap())
+                .unwrap();
+        jwk.include_point().unwrap();
+        jwk.include_bits().unwrap();
+        let value = serde_json::to_value(&jwk).unwrap();
+        let definition = definition(&schema, value["kty"].as_str().unwrap());
+
+        for member in value.as_object().unwrap().keys() {
+            assert!(
+                definition["properties"].get(member).is_some(),
+                "{name} `{member}`"
+            );
+        }
+        for member in definition["required"].as_array().unwrap() {
+            assert!(
+                value.get(member.as_str().unwrap()).is_some(),
+                "{name} `{member}`"
+            );
+        }
+    }
+}
+
+#[test]
+fn typescript_declares_every_key_type() {
+    let typescript = typescript();
+
+    for name in ["EcOutput", "RsaOutput", "OkpOutput", "OctOutput", "JwkSet"] {
+        assert!(
+            typescript.contains(&format!("export interface {name} {{")),
+            "{name}"
+        );
+    }
+    assert!(typescript.contains("export type Jwk = EcOutput | RsaOutput | OkpOutput | OctOutput;"));
+    assert!(typescript.contains("  \"x5t#S256\"?: string;"));
+}
@@ -0,0 +1,19 @@
+//! `SortedJwk` serializes the members in the canonical order.
+
+use std::fs;
+
+use pem_params::{Jwk, MEMBER_ORDER, SortedJwk};
+
+#[test]
+fn members_follow_canonical_order() {
+    let jwk: Jwk = serde_json::from_slice(&fs::read("tests/fixtures/rsa.json").unwrap()).unwrap();
+
+    let json = serde_json::to_string(&SortedJwk(&jwk)).unwrap();
+
+    let positions: Vec<usize> = MEMBER_ORDER
+        .iter()
+        .filter_map(|member| json.find(&format!("\"{member}\":")))
+        .collect();
+    assert_eq!(positions.len(), 11);
+    assert!(positions.is_sorted());
+}
@@ -0,0 +1,82 @@
+//! OpenSSH public keys convert to the same JWK as their PEM.
+
+use std::fs;
+
+use base64ct::{Base64, Encoding};
+use pem_params::{Error, ssh_to_jwk};
+use serde_json::Value;
+
+fn assert_converts(name: &str) {
+    let line = fs::read(format!("tests/fixtures/{name}.ssh.pub")).unwrap();
+    let expected: Value =
+        serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.pub.json")).unwrap())
+            .unwrap();
+
+    let jwk = ssh_to_jwk(&line).unwrap();
+
+    assert_eq!(serde_json::to_value(&jwk).unwrap(), expected);
+}
+
+#[test]
+fn ed25519() {
+    assert_converts("ed25519");
+}
+
+#[test]
+fn ecdsa_nistp256() {
+    assert_converts("ec-p256");
+}
+
+#[test]
+fn rsa() {
+    assert_converts("rsa");
+}
+
+#[test]
+fn rejects_mismatched_type() {
+    let line = fs::read_to_string("tests/fixtures/rsa.ssh.pub").unwrap();
+
+    assert!(matches!(
+        ssh_to_jwk(line.replacen("ssh-rsa", "ssh-ed25519", 1).as_bytes()),
+        Err(Error::InvalidSshKey(_))
+    ));
+}
+
+#[test]
+fn rejects_mismatched_curve_name() {
+    let line = fs::read_to_string("tests/fixtures/ec-p256.ssh.pub").unwrap();
+    let mut fields = line.split_whitespace();
+    let key_type = fields.next().unwrap();
+    let blob = Base64::decode_vec(fields.next().unwrap()).unwrap();
+
+    // The curve name is the second string of the blob, after the key type.
+    let tampered = [
+        &blob[..4 + key_type.len()],
+        b"\0\0\0\x08nistp384",
+        &blob[4 + key_type.len() + 12..],
+    ]
+    .concat();
+    let line = format!("{key_type} {}", Base64::encode_string(&tampered));
+
+    assert!(matches!(
+        ssh_to_jwk(line.as_bytes()),
+        Err(Error::InvalidSshKey(
+            "the curve name does not match the key type"
+        ))
+    ));
+}
+
+#[test]
+fn rejects_short_ed25519_key() {
+    let blob = [
+        b"\0\0\0\x0bssh-ed25519".as_slice(),
+        b"\0\0\0\x03\x01\x02\x03",
+    ]
+    .concat();
+    let line = format!("ssh-ed25519 {}", Base64::encode_string(&blob));
+
+    assert!(matches!(
+        ssh_to_jwk(line.as_bytes()),
+        Err(Error::InvalidSshKey("an Ed25519 key must be 32 bytes"))
+    ));
+}
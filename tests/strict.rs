@@ -0,0 +1,49 @@
+//! Strict mode rejects weak RSA keys and keys whose `alg` is guessed.
+
+use std::fs;
+
+use pem_params::{Error, pem_to_jwk, secret_to_jwk};
+
+#[test]
+fn rsa_bits() {
+    let pem = fs::read("tests/fixtures/rsa.pub.pem").unwrap();
+
+    let jwk = pem_to_jwk(&pem, false).unwrap();
+
+    assert_eq!(jwk.rsa_bits().unwrap(), Some(2048));
+    assert!(jwk.check_rsa_bits(2048).is_ok());
+}
+
+#[test]
+fn rejects_weak_rsa_key() {
+    let pem = fs::read("tests/fixtures/rsa-1024.pub.pem").unwrap();
+
+    let jwk = pem_to_jwk(&pem, false).unwrap();
+
+    assert!(matches!(
+        jwk.check_rsa_bits(2048),
+        Err(Error::WeakRsaKey {
+            bits: 1024,
+            min_bits: 2048
+        })
+    ));
+}
+
+#[test]
+fn rejects_guessed_alg() {
+    let rsa = pem_to_jwk(&fs::read("tests/fixtures/rsa.pub.pem").unwrap(), false).unwrap();
+    let pss = pem_to_jwk(&fs::read("tests/fixtures/rsa-pss.pem").unwrap(), true).unwrap();
+    let ec = pem_to_jwk(&fs::read("tests/fixtures/ec-p256.pub.pem").unwrap(), false).unwrap();
+    let oct = secret_to_jwk(b"secret").unwrap();
+
+    assert!(matches!(
+        rsa.check_alg_derived(),
+        Err(Error::AmbiguousAlg("RSA"))
+    ));
+    assert!(pss.check_alg_derived().is_ok());
+    assert!(ec.check_alg_derived().is_ok());
+    assert!(matches!(
+        oct.check_alg_derived(),
+        Err(Error::AmbiguousAlg("oct"))
+    ));
+}
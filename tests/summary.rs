@@ -0,0 +1,38 @@
+//! The summary describes the key without its private members.
+
+use std::fs;
+
+use pem_params::{pem_to_jwk, secret_to_jwk};
+
+#[test]
+fn public_key() {
+    let jwk = pem_to_jwk(&fs::read("tests/fixtures/ec-p256.pub.pem").unwrap(), false).unwrap();
+
+    assert_eq!(
+        jwk.summary().unwrap(),
+        format!("EC P-256 public key, kid={}, alg=ES256", jwk.kid().unwrap())
+    );
+}
+
+#[test]
+fn private_key() {
+    let jwk = pem_to_jwk(&fs::read("tests/fixtures/rsa.pem").unwrap(), true).unwrap();
+
+    assert_eq!(
+        jwk.summary().unwrap(),
+        format!(
+            "RSA 2048-bit key (private), kid={}, alg=RS256",
+            jwk.kid().unwrap()
+        )
+    );
+}
+
+#[test]
+fn secret_key() {
+    let jwk = secret_to_jwk(b"secret").unwrap();
+
+    let summary = jwk.summary().unwrap();
+
+    assert!(summary.starts_with("oct 48-bit key (private), kid="));
+    assert!(!summary.contains("c2VjcmV0"));
+}
@@ -0,0 +1,133 @@
+//! The thumbprint of each key type matches its computed `kid`, and identifies a key in a set.
+
+use std::fs;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use pem_params::{Jwk, JwkSet, KidFormat, ThumbprintHash, secret_to_jwk};
+
+fn assert_matches_kid(name: &str) {
+    let jwk: Jwk =
+        serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.json")).unwrap()).unwrap();
+
+    let thumbprint = jwk.thumbprint(ThumbprintHash::Sha256).unwrap();
+
+    assert_eq!(Some(thumbprint.as_str()), jwk.kid());
+}
+
+#[test]
+fn ec() {
+    assert_matches_kid("ec-p256");
+}
+
+#[test]
+fn rsa() {
+    assert_matches_kid("rsa");
+}
+
+#[test]
+fn okp() {
+    assert_matches_kid("ed25519.pub");
+    assert_matches_kid("ed448.pub");
+    assert_matches_kid("x25519.pub");
+}
+
+#[test]
+fn oct() {
+    let jwk = secret_to_jwk(b"secret").unwrap();
+
+    let thumbprint = jwk.thumbprint(ThumbprintHash::Sha256).unwrap();
+
+    assert_eq!(Some(thumbprint.as_str()), jwk.kid());
+}
+
+#[test]
+fn sha512_is_longer() {
+    let jwk: Jwk =
+        serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.json").unwrap()).unwrap();
+
+    assert_eq!(jwk.thumbprint(ThumbprintHash::Sha512).unwrap().len(), 86);
+}
+
+#[test]
+fn thumbprint_uri() {
+    let mut jwk: Jwk =
+        serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.json").unwrap()).unwrap();
+    let thumbprint = jwk.thumbprint(ThumbprintHash::Sha256).unwrap();
+
+    jwk.set_kid_format(KidFormat::ThumbprintUri).unwrap();
+
+    assert_eq!(
+        jwk.kid(),
+        Some(format!("urn:ietf:params:oauth:jwk-thumbprint:sha-256:{thumbprint}").as_str())
+    );
+}
+
+#[test]
+fn kid_hash() {
+    for name in ["ec-p256", "rsa", "ed25519.pub"] {
+        for (hash, length) in [
+            (ThumbprintHash::Sha256, 43),
+            (ThumbprintHash::Sha384, 64),
+            (ThumbprintHash::Sha512, 86),
+        ] {
+            let mut jwk: Jwk =
+                serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.json")).unwrap())
+                    .unwrap();
+
+            jwk.set_kid_hash(hash).unwrap();
+            assert_eq!(jwk.kid().unwrap().len(), length, "{name} {hash:?}");
+
+            jwk.set_kid_format(KidFormat::ThumbprintUri).unwrap();
+            assert!(
+                jwk.kid().unwrap().starts_with(&format!(
+                    "urn:ietf:params:oauth:jwk-thumbprint:{}:",
+                    hash.name()
+                )),
+                "{name} {hash:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn hex_kid_uses_kid_hash() {
+    for (hash, length) in [
+        (ThumbprintHash::Sha256, 64),
+        (ThumbprintHash::Sha384, 96),
+        (ThumbprintHash::Sha512, 128),
+    ] {
+        let mut jwk: Jwk =
+            serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.json").unwrap()).unwrap();
+        let thumbprint = Base64UrlUnpadded::decode_vec(&jwk.thumbprint(hash).unwrap()).unwrap();
+
+        jwk.set_kid_hash(hash).unwrap();
+        jwk.set_kid_format(KidFormat::Hex).unwrap();
+
+        let kid = jwk.kid().unwrap();
+        assert_eq!(kid.len(), length, "{hash:?}");
+        assert_eq!(
+            kid,
+            thumbprint
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>(),
+            "{hash:?}"
+        );
+    }
+}
+
+#[test]
+fn set_finds_key_by_thumbprint() {
+    let fixture = |name: &str| -> Jwk {
+        serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.json")).unwrap()).unwrap()
+    };
+    let mut published = fixture("ec-p256.pub");
+    published.set_kid("rotated-key".to_string());
+    let set = JwkSet {
+        keys: vec![fixture("rsa.pub"), published],
+    };
+
+    let found = set.find(&fixture("ec-p256")).unwrap().unwrap();
+    assert_eq!(found.kid(), Some("rotated-key"));
+    assert!(set.find(&fixture("ed25519.pub")).unwrap().is_none());
+}
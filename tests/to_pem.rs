@@ -0,0 +1,41 @@
+//! A JWK reconstructs the PEM it was converted from, in either PEM format.
+
+#![cfg(feature = "openssl")]
+
+use std::fs;
+
+use pem_params::{Jwk, PemFormat, pem_to_jwk};
+
+fn assert_round_trips(name: &str, is_private: bool, format: PemFormat, label: &str) {
+    let jwk: Jwk =
+        serde_json::from_slice(&fs::read(format!("tests/fixtures/{name}.json")).unwrap()).unwrap();
+
+    let pem = jwk.to_pem_as(format).unwrap();
+
+    assert!(
+        pem.starts_with(format!("-----BEGIN {label}-----").as_bytes()),
+        "{name} {format:?}"
+    );
+    assert_eq!(
+        serde_json::to_value(pem_to_jwk(&pem, is_private).unwrap()).unwrap(),
+        serde_json::to_value(&jwk).unwrap(),
+        "{name} {format:?}"
+    );
+}
+
+#[test]
+fn pkcs8() {
+    assert_round_trips("ec-p256", true, PemFormat::Pkcs8, "PRIVATE KEY");
+    assert_round_trips("ec-p256.pub", false, PemFormat::Pkcs8, "PUBLIC KEY");
+    assert_round_trips("rsa", true, PemFormat::Pkcs8, "PRIVATE KEY");
+    assert_round_trips("rsa.pub", false, PemFormat::Pkcs8, "PUBLIC KEY");
+}
+
+#[test]
+fn traditional() {
+    assert_round_trips("ec-p256", true, PemFormat::Traditional, "EC PRIVATE KEY");
+    assert_round_trips("ec-p256.pub", false, PemFormat::Traditional, "PUBLIC KEY");
+    assert_round_trips("rsa", true, PemFormat::Traditional, "RSA PRIVATE KEY");
+    assert_round_trips("rsa.pub", false, PemFormat::Traditional, "RSA PUBLIC KEY");
+    assert_round_trips("ed25519.pub", false, PemFormat::Traditional, "PUBLIC KEY");
+}
@@ -0,0 +1,52 @@
+//! Elliptic curve public points must lie on their curve, and the private members of an RSA key
+//! must agree with each other.
+
+use std::fs;
+
+use pem_params::{Error, Jwk};
+use serde_json::Value;
+
+fn fixture() -> Value {
+    serde_json::from_slice(&fs::read("tests/fixtures/ec-p256.pub.json").unwrap()).unwrap()
+}
+
+fn rsa_fixture() -> Value {
+    serde_json::from_slice(&fs::read("tests/fixtures/rsa.json").unwrap()).unwrap()
+}
+
+#[test]
+fn accepts_point_on_curve() {
+    let jwk: Jwk = serde_json::from_value(fixture()).unwrap();
+
+    assert!(jwk.validate().is_ok());
+}
+
+#[test]
+fn rejects_point_off_curve() {
+    let mut value = fixture();
+    value["y"] = Value::from("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAE");
+    let jwk: Jwk = serde_json::from_value(value).unwrap();
+
+    assert!(jwk.validate().is_err());
+}
+
+#[test]
+fn accepts_consistent_rsa_key() {
+    let jwk: Jwk = serde_json::from_value(rsa_fixture()).unwrap();
+
+    assert!(jwk.validate().is_ok());
+}
+
+#[test]
+fn rejects_inconsistent_rsa_key() {
+    for (member, from) in [("q", "p"), ("d", "dp"), ("dq", "dp"), ("qi", "dq")] {
+        let mut value = rsa_fixture();
+        value[member] = value[from].clone();
+        let jwk: Jwk = serde_json::from_value(value).unwrap();
+
+        assert!(
+            matches!(jwk.validate(), Err(Error::InconsistentRsaKey(_))),
+            "{member}"
+        );
+    }
+}
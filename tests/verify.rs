@@ -0,0 +1,32 @@
+//! Keys reconstructed from their JWK match the original key.
+
+#![cfg(feature = "openssl")]
+
+use std::fs;
+
+use pem_params::verify_pem;
+
+fn verify(name: &str) -> bool {
+    verify_pem(&fs::read(format!("tests/fixtures/{name}.pem")).unwrap()).unwrap()
+}
+
+#[test]
+fn roundtrips() {
+    for name in [
+        "ec-p256",
+        "ec-p256.pub",
+        "ec-p384",
+        "ec-p521",
+        "ec-secp256k1",
+        "rsa",
+        "rsa.pub",
+        "ed25519.pub",
+    ] {
+        assert!(verify(name), "{name} did not roundtrip");
+    }
+}
+
+#[test]
+fn rsa_pss_does_not_roundtrip() {
+    assert!(!verify("rsa-pss"));
+}